@@ -147,6 +147,12 @@ pub(crate) mod mode {
     /// CRTC related functions
     ioctl_readwrite!(get_crtc, DRM_IOCTL_BASE, 0xA1, drm_mode_crtc);
     ioctl_readwrite!(set_crtc, DRM_IOCTL_BASE, 0xA2, drm_mode_crtc);
+    ioctl_readwrite!(
+        crtc_get_sequence,
+        DRM_IOCTL_BASE,
+        0x3b,
+        drm_crtc_get_sequence
+    );
 
     /// Gamma related functions
     ioctl_readwrite!(get_gamma, DRM_IOCTL_BASE, 0xA4, drm_mode_crtc_lut);
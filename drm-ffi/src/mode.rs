@@ -51,6 +51,62 @@ pub fn get_resources(
     Ok(res)
 }
 
+/// Enumerate most card resources, reusing the allocations of the given buffers.
+///
+/// Unlike [`get_resources`], the ioctl is only issued a second time if any of
+/// the buffers is too small to hold the handles.
+pub fn get_resources_into(
+    fd: BorrowedFd<'_>,
+    fbs: &mut Vec<u32>,
+    crtcs: &mut Vec<u32>,
+    connectors: &mut Vec<u32>,
+    encoders: &mut Vec<u32>,
+) -> io::Result<drm_mode_card_res> {
+    loop {
+        fbs.clear();
+        crtcs.clear();
+        connectors.clear();
+        encoders.clear();
+
+        let mut res = drm_mode_card_res {
+            fb_id_ptr: fbs.as_mut_ptr() as _,
+            crtc_id_ptr: crtcs.as_mut_ptr() as _,
+            connector_id_ptr: connectors.as_mut_ptr() as _,
+            encoder_id_ptr: encoders.as_mut_ptr() as _,
+            count_fbs: fbs.capacity() as _,
+            count_crtcs: crtcs.capacity() as _,
+            count_connectors: connectors.capacity() as _,
+            count_encoders: encoders.capacity() as _,
+            ..Default::default()
+        };
+
+        unsafe {
+            ioctl::mode::get_resources(fd, &mut res)?;
+        }
+
+        let fits = res.count_fbs as usize <= fbs.capacity()
+            && res.count_crtcs as usize <= crtcs.capacity()
+            && res.count_connectors as usize <= connectors.capacity()
+            && res.count_encoders as usize <= encoders.capacity();
+
+        if fits {
+            // SAFETY: the kernel filled in all handles it reported
+            unsafe {
+                fbs.set_len(res.count_fbs as usize);
+                crtcs.set_len(res.count_crtcs as usize);
+                connectors.set_len(res.count_connectors as usize);
+                encoders.set_len(res.count_encoders as usize);
+            }
+            return Ok(res);
+        }
+
+        fbs.reserve(res.count_fbs as usize);
+        crtcs.reserve(res.count_crtcs as usize);
+        connectors.reserve(res.count_connectors as usize);
+        encoders.reserve(res.count_encoders as usize);
+    }
+}
+
 /// Enumerate plane resources.
 pub fn get_plane_resources(
     fd: BorrowedFd<'_>,
@@ -181,12 +237,24 @@ pub fn dirty_fb(
     fd: BorrowedFd<'_>,
     fb_id: u32,
     clips: &[drm_clip_rect],
+) -> io::Result<drm_mode_fb_dirty_cmd> {
+    dirty_fb_annotated(fd, fb_id, 0, 0, clips)
+}
+
+/// Mark a framebuffer as dirty, annotating the clips with a copy or fill operation.
+pub fn dirty_fb_annotated(
+    fd: BorrowedFd<'_>,
+    fb_id: u32,
+    flags: u32,
+    color: u32,
+    clips: &[drm_clip_rect],
 ) -> io::Result<drm_mode_fb_dirty_cmd> {
     let mut dirty = drm_mode_fb_dirty_cmd {
         fb_id,
+        flags,
+        color,
         num_clips: clips.len() as _,
         clips_ptr: clips.as_ptr() as _,
-        ..Default::default()
     };
 
     unsafe {
@@ -210,6 +278,20 @@ pub fn get_crtc(fd: BorrowedFd<'_>, crtc_id: u32) -> io::Result<drm_mode_crtc> {
     Ok(info)
 }
 
+/// Get the current vblank sequence of a CRTC and the time it started
+pub fn crtc_get_sequence(fd: BorrowedFd<'_>, crtc_id: u32) -> io::Result<drm_crtc_get_sequence> {
+    let mut info = drm_crtc_get_sequence {
+        crtc_id,
+        ..Default::default()
+    };
+
+    unsafe {
+        ioctl::mode::crtc_get_sequence(fd, &mut info)?;
+    }
+
+    Ok(info)
+}
+
 /// Set CRTC state
 pub fn set_crtc(
     fd: BorrowedFd<'_>,
@@ -762,6 +844,19 @@ pub fn atomic_commit(
     prop_counts: &mut [u32],
     props: &mut [u32],
     values: &mut [u64],
+) -> io::Result<()> {
+    atomic_commit_with_user_data(fd, flags, objs, prop_counts, props, values, 0)
+}
+
+/// Atomically set properties, passing `user_data` to the resulting page flip events
+pub fn atomic_commit_with_user_data(
+    fd: BorrowedFd<'_>,
+    flags: u32,
+    objs: &mut [u32],
+    prop_counts: &mut [u32],
+    props: &mut [u32],
+    values: &mut [u64],
+    user_data: u64,
 ) -> io::Result<()> {
     let mut atomic = drm_mode_atomic {
         flags,
@@ -770,6 +865,7 @@ pub fn atomic_commit(
         count_props_ptr: prop_counts.as_mut_ptr() as _,
         props_ptr: props.as_mut_ptr() as _,
         prop_values_ptr: values.as_mut_ptr() as _,
+        user_data,
         ..Default::default()
     };
 
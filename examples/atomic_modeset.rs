@@ -6,39 +6,35 @@ use drm::Device as BasicDevice;
 
 use drm::buffer::DrmFourcc;
 
-use drm::control::{self, atomic, connector, crtc, property, AtomicCommitFlags};
+use drm::control::{atomic, crtc, property, AtomicCommitFlags};
 
 pub fn main() {
     let card = Card::open_global();
 
-    card.set_client_capability(drm::ClientCapability::UniversalPlanes, true)
-        .expect("Unable to request UniversalPlanes capability");
-    card.set_client_capability(drm::ClientCapability::Atomic, true)
-        .expect("Unable to request Atomic capability");
+    card.set_client_capabilities(&[
+        (drm::ClientCapability::UniversalPlanes, true),
+        (drm::ClientCapability::Atomic, true),
+    ])
+    .expect("Unable to request client capabilities");
 
     // Load the information.
     let res = card
         .resource_handles()
         .expect("Could not load normal resource ids.");
-    let coninfo: Vec<connector::Info> = res
-        .connectors()
-        .iter()
-        .flat_map(|con| card.get_connector(*con, true))
-        .collect();
     let crtcinfo: Vec<crtc::Info> = res
         .crtcs()
         .iter()
         .flat_map(|crtc| card.get_crtc(*crtc))
         .collect();
 
-    // Filter each connector until we find one that's connected.
-    let con = coninfo
-        .iter()
-        .find(|&i| i.state() == connector::State::Connected)
-        .expect("No connected connectors");
+    // Find the first connector that's connected.
+    let coninfo = res
+        .connected_connectors(&card, true)
+        .expect("Could not load connectors");
+    let con = coninfo.first().expect("No connected connectors");
 
-    // Get the first (usually best) mode
-    let &mode = con.modes().first().expect("No modes found on connector");
+    // Get the preferred mode of the display
+    let &mode = con.preferred_mode().expect("No modes found on connector");
 
     let (disp_width, disp_height) = mode.size();
 
@@ -70,33 +66,13 @@ pub fn main() {
         .add_framebuffer(&db, 24, 32)
         .expect("Could not create FB");
 
-    let planes = card.plane_handles().expect("Could not list planes");
-    let (better_planes, compatible_planes): (
-        Vec<control::plane::Handle>,
-        Vec<control::plane::Handle>,
-    ) = planes
-        .iter()
-        .filter(|&&plane| {
-            card.get_plane(plane)
-                .map(|plane_info| {
-                    let compatible_crtcs = res.filter_crtcs(plane_info.possible_crtcs());
-                    compatible_crtcs.contains(&crtc.handle())
-                })
-                .unwrap_or(false)
-        })
-        .partition(|&&plane| {
-            if let Ok(props) = card.get_properties(plane) {
-                for (&id, &val) in props.iter() {
-                    if let Ok(info) = card.get_property(id) {
-                        if info.name().to_str().map(|x| x == "type").unwrap_or(false) {
-                            return val == (drm::control::PlaneType::Primary as u32).into();
-                        }
-                    }
-                }
-            }
-            false
-        });
-    let plane = *better_planes.first().unwrap_or(&compatible_planes[0]);
+    // Primary planes are listed first
+    let plane = card
+        .planes_for_crtc(crtc.handle())
+        .expect("Could not list planes")
+        .first()
+        .expect("No compatible planes found")
+        .handle();
 
     println!("{:#?}", mode);
     println!("{:#?}", fb);
@@ -108,11 +84,6 @@ pub fn main() {
         .expect("Could not get props of connector")
         .as_hashmap(&card)
         .expect("Could not get a prop from connector");
-    let crtc_props = card
-        .get_properties(crtc.handle())
-        .expect("Could not get props of crtc")
-        .as_hashmap(&card)
-        .expect("Could not get a prop from crtc");
     let plane_props = card
         .get_properties(plane)
         .expect("Could not get props of plane")
@@ -125,15 +96,9 @@ pub fn main() {
         con_props["CRTC_ID"].handle(),
         property::Value::CRTC(Some(crtc.handle())),
     );
-    let blob = card
-        .create_property_blob(&mode)
-        .expect("Failed to create blob");
-    atomic_req.add_property(crtc.handle(), crtc_props["MODE_ID"].handle(), blob);
-    atomic_req.add_property(
-        crtc.handle(),
-        crtc_props["ACTIVE"].handle(),
-        property::Value::Boolean(true),
-    );
+    atomic_req
+        .set_crtc_mode(&card, crtc.handle(), mode)
+        .expect("Failed to stage mode");
     atomic_req.add_property(
         plane,
         plane_props["FB_ID"].handle(),
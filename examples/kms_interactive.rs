@@ -38,15 +38,11 @@ fn run_repl(card: &Card) {
 
         // Load the image into the buffer
         {
+            let pitch = db.pitch();
             let mut mapping = card.map_dumb_buffer(&mut db).unwrap();
-            let buffer = mapping.as_mut();
-            for (img_px, map_px) in image.pixels().zip(buffer.chunks_exact_mut(4)) {
-                // Assuming little endian, it's BGRA
-                map_px[0] = img_px[0]; // Blue
-                map_px[1] = img_px[1]; // Green
-                map_px[2] = img_px[2]; // Red
-                map_px[3] = img_px[3]; // Alpha
-            }
+            mapping
+                .copy_from_rgba(image.as_raw(), image.width(), image.height(), pitch)
+                .unwrap();
         };
     }
 
@@ -5,7 +5,7 @@ use drm::control::Device as ControlDevice;
 
 use drm::buffer::DrmFourcc;
 
-use drm::control::{connector, crtc};
+use drm::control::crtc;
 
 pub fn main() {
     let card = Card::open_global();
@@ -14,25 +14,20 @@ pub fn main() {
     let res = card
         .resource_handles()
         .expect("Could not load normal resource ids.");
-    let coninfo: Vec<connector::Info> = res
-        .connectors()
-        .iter()
-        .flat_map(|con| card.get_connector(*con, true))
-        .collect();
     let crtcinfo: Vec<crtc::Info> = res
         .crtcs()
         .iter()
         .flat_map(|crtc| card.get_crtc(*crtc))
         .collect();
 
-    // Filter each connector until we find one that's connected.
-    let con = coninfo
-        .iter()
-        .find(|&i| i.state() == connector::State::Connected)
-        .expect("No connected connectors");
+    // Find the first connector that's connected.
+    let coninfo = res
+        .connected_connectors(&card, true)
+        .expect("Could not load connectors");
+    let con = coninfo.first().expect("No connected connectors");
 
-    // Get the first (usually best) mode
-    let &mode = con.modes().first().expect("No modes found on connector");
+    // Get the preferred mode of the display
+    let &mode = con.preferred_mode().expect("No modes found on connector");
 
     let (disp_width, disp_height) = mode.size();
 
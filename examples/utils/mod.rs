@@ -46,6 +46,7 @@ pub mod capabilities {
         DC::Prime,
         DC::MonotonicTimestamp,
         DC::ASyncPageFlip,
+        DC::AtomicASyncPageFlip,
         DC::CursorWidth,
         DC::CursorHeight,
         DC::AddFB2Modifiers,
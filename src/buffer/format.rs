@@ -0,0 +1,137 @@
+//! Memory layout information of common pixel formats.
+
+use super::DrmFourcc;
+
+/// Memory layout of a pixel format, mirroring the kernel's `drm_format_info`.
+///
+/// Only formats without block-based layouts are described.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct FormatInfo {
+    format: DrmFourcc,
+    depth: u8,
+    num_planes: u8,
+    cpp: [u8; 3],
+    hsub: u8,
+    vsub: u8,
+    has_alpha: bool,
+    is_yuv: bool,
+}
+
+impl FormatInfo {
+    /// Returns the layout of the given format, or [`None`] if it is not known
+    /// to drm-rs.
+    pub fn from_format(format: DrmFourcc) -> Option<FormatInfo> {
+        use DrmFourcc as F;
+
+        #[rustfmt::skip]
+        let (depth, num_planes, cpp, hsub, vsub, has_alpha, is_yuv) = match format {
+            F::C8 | F::Rgb332 | F::Bgr233 => (8, 1, [1, 0, 0], 1, 1, false, false),
+            F::R8 => (0, 1, [1, 0, 0], 1, 1, false, false),
+            F::R16 | F::Rg88 | F::Gr88 => (0, 1, [2, 0, 0], 1, 1, false, false),
+            F::Rg1616 | F::Gr1616 => (0, 1, [4, 0, 0], 1, 1, false, false),
+            F::Xrgb4444 | F::Xbgr4444 | F::Rgbx4444 | F::Bgrx4444 => (0, 1, [2, 0, 0], 1, 1, false, false),
+            F::Argb4444 | F::Abgr4444 | F::Rgba4444 | F::Bgra4444 => (0, 1, [2, 0, 0], 1, 1, true, false),
+            F::Xrgb1555 | F::Xbgr1555 | F::Rgbx5551 | F::Bgrx5551 => (15, 1, [2, 0, 0], 1, 1, false, false),
+            F::Argb1555 | F::Abgr1555 | F::Rgba5551 | F::Bgra5551 => (15, 1, [2, 0, 0], 1, 1, true, false),
+            F::Rgb565 | F::Bgr565 => (16, 1, [2, 0, 0], 1, 1, false, false),
+            F::Rgb888 | F::Bgr888 => (24, 1, [3, 0, 0], 1, 1, false, false),
+            F::Xrgb8888 | F::Xbgr8888 | F::Rgbx8888 | F::Bgrx8888 => (24, 1, [4, 0, 0], 1, 1, false, false),
+            F::Argb8888 | F::Abgr8888 | F::Rgba8888 | F::Bgra8888 => (32, 1, [4, 0, 0], 1, 1, true, false),
+            F::Xrgb2101010 | F::Xbgr2101010 | F::Rgbx1010102 | F::Bgrx1010102 => (30, 1, [4, 0, 0], 1, 1, false, false),
+            F::Argb2101010 | F::Abgr2101010 | F::Rgba1010102 | F::Bgra1010102 => (30, 1, [4, 0, 0], 1, 1, true, false),
+            F::Xrgb16161616f | F::Xbgr16161616f => (0, 1, [8, 0, 0], 1, 1, false, false),
+            F::Argb16161616f | F::Abgr16161616f => (0, 1, [8, 0, 0], 1, 1, true, false),
+            F::Xyuv8888 => (0, 1, [4, 0, 0], 1, 1, false, true),
+            F::Ayuv => (0, 1, [4, 0, 0], 1, 1, true, true),
+            F::Yuyv | F::Yvyu | F::Uyvy | F::Vyuy => (0, 1, [2, 0, 0], 2, 1, false, true),
+            F::Nv12 | F::Nv21 => (0, 2, [1, 2, 0], 2, 2, false, true),
+            F::Nv16 | F::Nv61 => (0, 2, [1, 2, 0], 2, 1, false, true),
+            F::Nv24 | F::Nv42 => (0, 2, [1, 2, 0], 1, 1, false, true),
+            F::P010 | F::P012 | F::P016 => (0, 2, [2, 4, 0], 2, 2, false, true),
+            F::P210 => (0, 2, [2, 4, 0], 2, 1, false, true),
+            F::Yuv410 | F::Yvu410 => (0, 3, [1, 1, 1], 4, 4, false, true),
+            F::Yuv411 | F::Yvu411 => (0, 3, [1, 1, 1], 4, 1, false, true),
+            F::Yuv420 | F::Yvu420 => (0, 3, [1, 1, 1], 2, 2, false, true),
+            F::Yuv422 | F::Yvu422 => (0, 3, [1, 1, 1], 2, 1, false, true),
+            F::Yuv444 | F::Yvu444 => (0, 3, [1, 1, 1], 1, 1, false, true),
+            _ => return None,
+        };
+
+        Some(FormatInfo {
+            format,
+            depth,
+            num_planes,
+            cpp,
+            hsub,
+            vsub,
+            has_alpha,
+            is_yuv,
+        })
+    }
+
+    /// Returns the format described.
+    pub fn format(&self) -> DrmFourcc {
+        self.format
+    }
+
+    /// Returns the color depth used to describe this format to the legacy
+    /// framebuffer API, or [`None`] if the format has no such depth.
+    pub fn depth(&self) -> Option<u32> {
+        match self.depth {
+            0 => None,
+            depth => Some(depth as u32),
+        }
+    }
+
+    /// Returns the number of planes of this format.
+    pub fn num_planes(&self) -> usize {
+        self.num_planes as usize
+    }
+
+    /// Returns the number of bytes per pixel of the given plane, or 0 if the
+    /// format has no such plane.
+    pub fn cpp(&self, plane: usize) -> u32 {
+        self.cpp.get(plane).copied().unwrap_or(0) as u32
+    }
+
+    /// Returns the number of bits per pixel of the given plane, or 0 if the
+    /// format has no such plane.
+    pub fn bpp(&self, plane: usize) -> u32 {
+        self.cpp(plane) * 8
+    }
+
+    /// Returns the horizontal and vertical chroma subsampling factors.
+    ///
+    /// All planes but the first are subsampled by these factors.
+    pub fn subsampling(&self) -> (u32, u32) {
+        (self.hsub as u32, self.vsub as u32)
+    }
+
+    /// Returns the width in pixels of the given plane, for a buffer of `width` pixels.
+    pub fn plane_width(&self, width: u32, plane: usize) -> u32 {
+        if plane == 0 {
+            width
+        } else {
+            (width + self.hsub as u32 - 1) / self.hsub as u32
+        }
+    }
+
+    /// Returns the height in pixels of the given plane, for a buffer of `height` pixels.
+    pub fn plane_height(&self, height: u32, plane: usize) -> u32 {
+        if plane == 0 {
+            height
+        } else {
+            (height + self.vsub as u32 - 1) / self.vsub as u32
+        }
+    }
+
+    /// Returns whether this format has an alpha channel.
+    pub fn has_alpha(&self) -> bool {
+        self.has_alpha
+    }
+
+    /// Returns whether this is a YUV format.
+    pub fn is_yuv(&self) -> bool {
+        self.is_yuv
+    }
+}
@@ -16,17 +16,25 @@
 //! There are two methods of sharing a GEM handle between processes:
 //!
 //! 1. Using `Flink` to globally publish a handle using a 32-bit 'name'. This
-//! requires either holding the DRM Master lock or having the process'
-//! [`AuthToken`](struct@crate::AuthToken) authenticated. However, any process can
-//! open these handles if they know (or even guess) the global name.
+//!    requires either holding the DRM Master lock or having the process'
+//!    [`AuthToken`](struct@crate::AuthToken) authenticated. However, any process can
+//!    open these handles if they know (or even guess) the global name.
 //!
 //! 2. Converting the GEM handle into a PRIME file descriptor, and passing it
-//! like a regular one. This allows better control and security, and is the
-//! recommended method of sharing buffers.
+//!    like a regular one. This allows better control and security, and is the
+//!    recommended method of sharing buffers.
 
 use crate::control;
 pub use drm_fourcc::{DrmFourcc, DrmModifier, DrmVendor, UnrecognizedFourcc, UnrecognizedVendor};
 
+use std::io;
+use std::os::unix::io::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+
+use rustix::ioctl::{ioctl, ReadWriteOpcode, Setter, Updater, WriteOpcode};
+
+mod format;
+pub use self::format::FormatInfo;
+
 /// A handle to a GEM buffer
 ///
 /// # Notes
@@ -36,6 +44,11 @@ pub use drm_fourcc::{DrmFourcc, DrmModifier, DrmVendor, UnrecognizedFourcc, Unre
 /// prevent buffers from leaking by properly closing them after they are done.
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -66,6 +79,19 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "buffer::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// The name of a GEM buffer.
 ///
 /// # Notes
@@ -118,3 +144,201 @@ pub trait PlanarBuffer {
     /// The offsets of the buffer.
     fn offsets(&self) -> [u32; 4];
 }
+
+/// Returns the vendor encoded in the top 8 bits of a modifier.
+///
+/// Returns `Ok(None)` for vendor-neutral modifiers, such as
+/// [`DrmModifier::Linear`].
+pub fn modifier_vendor(modifier: DrmModifier) -> Result<Option<DrmVendor>, UnrecognizedVendor> {
+    modifier.vendor()
+}
+
+/// Returns the vendor-specific code encoded in the lower 56 bits of a modifier.
+pub fn modifier_code(modifier: DrmModifier) -> u64 {
+    u64::from(modifier) & MODIFIER_CODE_MASK
+}
+
+/// Builds a modifier from a vendor and a vendor-specific code.
+///
+/// Only the lower 56 bits of `code` are used.
+pub fn modifier_with_vendor(vendor: DrmVendor, code: u64) -> DrmModifier {
+    DrmModifier::from(((vendor as u64) << 56) | (code & MODIFIER_CODE_MASK))
+}
+
+const MODIFIER_CODE_MASK: u64 = (1 << 56) - 1;
+
+/// Returns the size in bytes of the buffer referred to by a dma-buf file
+/// descriptor, such as one passed to
+/// [`Device::prime_fd_to_buffer`](crate::control::Device::prime_fd_to_buffer).
+///
+/// The size is queried by seeking to the end of the file, which Linux dma-bufs
+/// support without affecting their contents. Other kinds of file descriptors
+/// may report a different size or fail.
+pub fn dma_buf_size(fd: BorrowedFd<'_>) -> io::Result<u64> {
+    let size = rustix::fs::seek(fd, rustix::fs::SeekFrom::End(0))?;
+    Ok(size)
+}
+
+bitflags::bitflags! {
+    /// Access to a dma-buf that a sync file is exported for or imported as,
+    /// see [`export_sync_file`] and [`import_sync_file`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct SyncFileFlags : u32 {
+        /// Read access
+        const READ = 1 << 0;
+        /// Write access
+        const WRITE = 1 << 1;
+    }
+}
+
+/// Argument of the `DMA_BUF_IOCTL_EXPORT_SYNC_FILE` and
+/// `DMA_BUF_IOCTL_IMPORT_SYNC_FILE` ioctls.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+struct DmaBufSyncFile {
+    flags: u32,
+    fd: i32,
+}
+
+const DMA_BUF_BASE: u8 = b'b';
+
+/// `DMA_BUF_IOCTL_EXPORT_SYNC_FILE`
+type ExportSyncFileOpcode = ReadWriteOpcode<DMA_BUF_BASE, 2, DmaBufSyncFile>;
+/// `DMA_BUF_IOCTL_IMPORT_SYNC_FILE`
+type ImportSyncFileOpcode = WriteOpcode<DMA_BUF_BASE, 3, DmaBufSyncFile>;
+
+/// Exports the implicit fences of a dma-buf as a sync file.
+///
+/// With [`SyncFileFlags::READ`], the returned sync file signals once all
+/// pending writes to the buffer have finished, so it is safe to read from.
+/// With [`SyncFileFlags::WRITE`], it also waits for pending reads, so it is
+/// safe to write to.
+///
+/// Requires Linux 6.0 or newer.
+pub fn export_sync_file(dmabuf: BorrowedFd<'_>, flags: SyncFileFlags) -> io::Result<OwnedFd> {
+    let mut arg = DmaBufSyncFile {
+        flags: flags.bits(),
+        fd: -1,
+    };
+
+    unsafe {
+        ioctl(
+            dmabuf,
+            Updater::<ExportSyncFileOpcode, DmaBufSyncFile>::new(&mut arg),
+        )?;
+        Ok(OwnedFd::from_raw_fd(arg.fd))
+    }
+}
+
+/// Imports a sync file into the implicit fences of a dma-buf.
+///
+/// With [`SyncFileFlags::READ`], the fence is added as a read, so later
+/// writers implicitly wait for it. With [`SyncFileFlags::WRITE`], it is added
+/// as a write, so all later users wait for it.
+///
+/// Requires Linux 6.0 or newer.
+pub fn import_sync_file(
+    dmabuf: BorrowedFd<'_>,
+    sync_file: BorrowedFd<'_>,
+    flags: SyncFileFlags,
+) -> io::Result<()> {
+    let arg = DmaBufSyncFile {
+        flags: flags.bits(),
+        fd: sync_file.as_raw_fd(),
+    };
+
+    unsafe {
+        ioctl(
+            dmabuf,
+            Setter::<ImportSyncFileOpcode, DmaBufSyncFile>::new(arg),
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "buffer::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    #[test]
+    fn intel_modifier() {
+        let modifier = DrmModifier::I915_x_tiled;
+        assert_eq!(modifier_vendor(modifier), Ok(Some(DrmVendor::Intel)));
+        assert_eq!(modifier_code(modifier), 1);
+        assert_eq!(modifier_with_vendor(DrmVendor::Intel, 1), modifier);
+    }
+
+    #[test]
+    fn amd_modifier() {
+        // AMD_FMT_MOD with TILE_VERSION GFX9 and TILE 64K_S_X
+        let raw = 0x0200_0000_0000_0901;
+        let modifier = DrmModifier::from(raw);
+        assert_eq!(modifier_vendor(modifier), Ok(Some(DrmVendor::Amd)));
+        assert_eq!(modifier_code(modifier), 0x901);
+        assert_eq!(u64::from(modifier_with_vendor(DrmVendor::Amd, 0x901)), raw);
+    }
+
+    #[test]
+    fn vendor_neutral_modifier() {
+        assert_eq!(modifier_vendor(DrmModifier::Linear), Ok(None));
+        assert_eq!(modifier_code(DrmModifier::Linear), 0);
+    }
+
+    #[test]
+    fn modifier_code_is_truncated() {
+        let modifier = modifier_with_vendor(DrmVendor::Intel, u64::MAX);
+        assert_eq!(modifier_vendor(modifier), Ok(Some(DrmVendor::Intel)));
+        assert_eq!(modifier_code(modifier), MODIFIER_CODE_MASK);
+    }
+
+    #[test]
+    fn sync_file_flags() {
+        // DMA_BUF_SYNC_READ and DMA_BUF_SYNC_WRITE of linux/dma-buf.h
+        assert_eq!(SyncFileFlags::READ.bits(), 1);
+        assert_eq!(SyncFileFlags::WRITE.bits(), 2);
+        assert_eq!((SyncFileFlags::READ | SyncFileFlags::WRITE).bits(), 3);
+    }
+
+    #[test]
+    fn sync_file_ioctls() {
+        use rustix::ioctl::CompileTimeOpcode;
+
+        // struct dma_buf_export_sync_file and dma_buf_import_sync_file
+        assert_eq!(std::mem::size_of::<DmaBufSyncFile>(), 8);
+        assert_eq!(std::mem::align_of::<DmaBufSyncFile>(), 4);
+
+        assert_eq!(u64::from(ExportSyncFileOpcode::OPCODE.raw()), 0xc008_6202);
+        assert_eq!(u64::from(ImportSyncFileOpcode::OPCODE.raw()), 0x4008_6203);
+    }
+
+    #[test]
+    fn sync_file_on_non_dmabuf() {
+        use std::os::unix::io::AsFd;
+
+        let device = crate::mock::MockDevice::new().unwrap();
+        let fd = device.as_fd();
+        assert!(export_sync_file(fd, SyncFileFlags::READ).is_err());
+        assert!(import_sync_file(fd, fd, SyncFileFlags::WRITE).is_err());
+    }
+
+    #[test]
+    fn dma_buf_size_of_memfd() {
+        use rustix::fs::{ftruncate, memfd_create, MemfdFlags};
+        use std::os::unix::io::AsFd;
+
+        let memfd = memfd_create("drm-rs-test", MemfdFlags::CLOEXEC).unwrap();
+        ftruncate(&memfd, 4096 * 3).unwrap();
+        assert_eq!(dma_buf_size(memfd.as_fd()).unwrap(), 4096 * 3);
+
+        ftruncate(&memfd, 0).unwrap();
+        assert_eq!(dma_buf_size(memfd.as_fd()).unwrap(), 0);
+    }
+}
@@ -2,13 +2,40 @@
 
 use crate::control;
 
+use std::io;
+
 /// Helper struct to construct atomic commit requests
-#[derive(Debug, Clone, Default)]
+///
+/// Property blobs created by [`AtomicModeReq::set_crtc_mode`] are owned by the
+/// request. They are destroyed once the request is committed using
+/// [`control::Device::atomic_commit`], as the kernel keeps its own reference to
+/// blobs in use. Requests can be tested without giving them up using
+/// [`control::Device::atomic_test`]. Requests that are never committed have to be
+/// released using [`AtomicModeReq::cleanup`], as the request has no access to the
+/// device when dropped.
+///
+/// Clones of a request stage the same properties, but do not own its blobs. They
+/// are only valid as long as the original request has been neither committed
+/// nor cleaned up.
+#[derive(Debug, Default)]
 pub struct AtomicModeReq {
     pub(super) objects: Vec<control::RawResourceHandle>,
     pub(super) count_props_per_object: Vec<u32>,
     pub(super) props: Vec<control::property::Handle>,
     pub(super) values: Vec<control::property::RawValue>,
+    pub(super) blobs: Vec<u64>,
+}
+
+impl Clone for AtomicModeReq {
+    fn clone(&self) -> Self {
+        AtomicModeReq {
+            objects: self.objects.clone(),
+            count_props_per_object: self.count_props_per_object.clone(),
+            props: self.props.clone(),
+            values: self.values.clone(),
+            blobs: Vec::new(),
+        }
+    }
 }
 
 impl AtomicModeReq {
@@ -17,6 +44,37 @@ impl AtomicModeReq {
         Self::default()
     }
 
+    /// Create a new and empty atomic commit request, with space reserved for
+    /// `objects` objects and `props` properties in total
+    ///
+    /// This avoids reallocations while staging properties. Requests can be
+    /// emptied for reuse using [`AtomicModeReq::clear`], keeping the reserved space.
+    pub fn with_capacity(objects: usize, props: usize) -> AtomicModeReq {
+        AtomicModeReq {
+            objects: Vec::with_capacity(objects),
+            count_props_per_object: Vec::with_capacity(objects),
+            props: Vec::with_capacity(props),
+            values: Vec::with_capacity(props),
+            blobs: Vec::new(),
+        }
+    }
+
+    /// Remove all staged properties, keeping the allocated capacity
+    ///
+    /// Property blobs owned by the request are destroyed like by
+    /// [`AtomicModeReq::cleanup`], as no staged property refers to them anymore.
+    /// The properties are removed even if destroying a blob fails.
+    pub fn clear<D>(&mut self, device: &D) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        self.objects.clear();
+        self.count_props_per_object.clear();
+        self.props.clear();
+        self.values.clear();
+        self.cleanup(device)
+    }
+
     /// Add a property and value pair for a given raw resource to the request
     pub fn add_raw_property(
         &mut self,
@@ -68,4 +126,291 @@ impl AtomicModeReq {
     {
         self.add_raw_property(handle.into(), property, value.into())
     }
+
+    /// Add all properties staged in `other` to this request
+    ///
+    /// Values of `other` override values of this request for the same property
+    /// of the same object. Property blobs owned by `other` are taken over by
+    /// this request.
+    pub fn merge(&mut self, other: AtomicModeReq) {
+        let mut props = other.props.iter().zip(other.values.iter());
+        for (&obj_id, &prop_count) in other
+            .objects
+            .iter()
+            .zip(other.count_props_per_object.iter())
+        {
+            for (&prop_id, &value) in props.by_ref().take(prop_count as usize) {
+                self.add_raw_property(obj_id, prop_id, value);
+            }
+        }
+        self.blobs.extend(other.blobs);
+    }
+
+    /// Stage a modeset of the given crtc to `mode`.
+    ///
+    /// This creates a property blob for the mode, which is owned by this request,
+    /// and sets the `MODE_ID` and `ACTIVE` properties of the crtc.
+    pub fn set_crtc_mode<D>(
+        &mut self,
+        device: &D,
+        crtc: control::crtc::Handle,
+        mode: control::Mode,
+    ) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        let mode_id = control::find_property(device, crtc, "MODE_ID")?;
+        let active = control::find_property(device, crtc, "ACTIVE")?;
+        let (mode_id, active) = match (mode_id, active) {
+            (Some((mode_id, _)), Some((active, _))) => (mode_id.handle(), active.handle()),
+            _ => return Err(rustix::io::Errno::NOENT.into()),
+        };
+
+        let blob = device.create_property_blob(&mode)?;
+        if let control::property::Value::Blob(id) = blob {
+            self.blobs.push(id);
+        }
+        self.add_property(crtc, mode_id, blob);
+        self.add_property(crtc, active, control::property::Value::Boolean(true));
+        Ok(())
+    }
+
+    /// Stage modesets of several crtcs, like [`AtomicModeReq::set_crtc_mode`] for each.
+    ///
+    /// Committing the request with
+    /// [`ALLOW_MODESET`](control::AtomicCommitFlags::ALLOW_MODESET) brings up all
+    /// of the crtcs at once. Each mode gets a blob of its own, which is owned by
+    /// this request. If staging fails, the blobs created so far are owned by the
+    /// request as well and can be released using [`AtomicModeReq::cleanup`].
+    pub fn set_crtc_modes<D>(
+        &mut self,
+        device: &D,
+        modes: &[(control::crtc::Handle, control::Mode)],
+    ) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        for &(crtc, mode) in modes {
+            self.set_crtc_mode(device, crtc, mode)?;
+        }
+        Ok(())
+    }
+
+    /// Stage the `zpos` property of a plane, controlling its stacking order.
+    ///
+    /// The supported range can be queried using
+    /// [`control::Device::plane_zpos_range`]. Fails with `ENOENT` if the plane
+    /// has no such property.
+    pub fn set_plane_zpos<D>(
+        &mut self,
+        device: &D,
+        plane: control::plane::Handle,
+        zpos: i64,
+    ) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        let value = control::property::Value::SignedRange(zpos).into();
+        control::add_property_by_name(device, self, plane, "zpos", value)
+    }
+
+    /// Stage the power state of a connector for atomic clients.
+    ///
+    /// The kernel rejects the legacy `DPMS` property in atomic commits, so this
+    /// sets the `ACTIVE` property of the crtc currently driving the connector
+    /// instead: [`DpmsState::On`](control::connector::DpmsState::On) enables the
+    /// crtc, every other state disables it. Fails with `ENOENT` if the connector
+    /// is not driven by a crtc.
+    pub fn set_connector_dpms<D>(
+        &mut self,
+        device: &D,
+        connector: control::connector::Handle,
+        state: control::connector::DpmsState,
+    ) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        let crtc = control::find_property(device, connector, "CRTC_ID")?
+            .and_then(|(_, crtc)| control::from_u32::<control::crtc::Handle>(crtc as u32))
+            .ok_or(rustix::io::Errno::NOENT)?;
+        let active = state == control::connector::DpmsState::On;
+        control::add_property_by_name(device, self, crtc, "ACTIVE", active.into())
+    }
+
+    /// Destroy all property blobs owned by this request.
+    ///
+    /// Properties referring to these blobs stay part of the request, so it should
+    /// not be committed afterwards.
+    pub fn cleanup<D>(&mut self, device: &D) -> io::Result<()>
+    where
+        D: control::Device + ?Sized,
+    {
+        let mut result = Ok(());
+        for blob in self.blobs.drain(..) {
+            if let Err(err) = device.destroy_property_blob(blob) {
+                result = Err(err);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AtomicModeReq;
+    use crate::control::connector::DpmsState;
+    use crate::control::tests::{blob_counter, handle, property_counter};
+    use crate::control::{AtomicCommitFlags, Device};
+
+    fn req_with_blob(blob: u64) -> AtomicModeReq {
+        let mut req = AtomicModeReq::new();
+        req.add_raw_property(handle(40), handle(7), blob);
+        req.blobs.push(blob);
+        req
+    }
+
+    #[test]
+    fn clone_does_not_own_blobs() {
+        let device = blob_counter();
+        let mut req = req_with_blob(3);
+
+        let mut clone = req.clone();
+        assert_eq!(clone.values, [3]);
+        clone.cleanup(&device).unwrap();
+        assert!(device.destroyed.borrow().is_empty());
+
+        req.cleanup(&device).unwrap();
+        req.cleanup(&device).unwrap();
+        assert_eq!(*device.destroyed.borrow(), [3]);
+    }
+
+    #[test]
+    fn clear_destroys_blobs() {
+        let device = blob_counter();
+        let mut req = req_with_blob(3);
+
+        req.clear(&device).unwrap();
+        assert!(req.objects.is_empty());
+        assert!(req.values.is_empty());
+        assert_eq!(*device.destroyed.borrow(), [3]);
+
+        req.cleanup(&device).unwrap();
+        assert_eq!(*device.destroyed.borrow(), [3]);
+    }
+
+    #[test]
+    fn commit_destroys_blobs() {
+        let device = blob_counter();
+
+        // The mock device fails the commit itself
+        let req = req_with_blob(3);
+        assert!(device
+            .atomic_commit(AtomicCommitFlags::TEST_ONLY, req)
+            .is_err());
+        assert_eq!(*device.destroyed.borrow(), [3]);
+    }
+
+    #[test]
+    fn test_keeps_blobs() {
+        let device = blob_counter();
+
+        let req = req_with_blob(3);
+        assert!(device
+            .atomic_test(AtomicCommitFlags::empty(), &req)
+            .is_err());
+        assert!(device.destroyed.borrow().is_empty());
+        assert_eq!(req.blobs, [3]);
+    }
+
+    #[test]
+    fn set_connector_dpms() {
+        let device = property_counter(None);
+
+        let mut req = AtomicModeReq::new();
+        req.set_connector_dpms(&device, handle(30), DpmsState::Suspend)
+            .unwrap();
+        assert_eq!(req.objects, [handle(40)]);
+        assert_eq!(req.props, [handle(6)]);
+        assert_eq!(req.values, [0]);
+
+        req.set_connector_dpms(&device, handle(30), DpmsState::On)
+            .unwrap();
+        assert_eq!(req.values, [1]);
+    }
+
+    #[test]
+    fn set_connector_dpms_disabled() {
+        let device = property_counter(None);
+
+        let mut req = AtomicModeReq::new();
+        let err = req
+            .set_connector_dpms(&device, handle(31), DpmsState::Off)
+            .unwrap_err();
+        assert_eq!(
+            err.raw_os_error(),
+            Some(rustix::io::Errno::NOENT.raw_os_error())
+        );
+        assert!(req.objects.is_empty());
+    }
+
+    #[test]
+    fn merge_overrides() {
+        let mut req = AtomicModeReq::new();
+        req.add_raw_property(handle(40), handle(6), 1);
+        req.add_raw_property(handle(40), handle(7), 2);
+        req.add_raw_property(handle(3), handle(1), 3);
+
+        let mut other = AtomicModeReq::new();
+        other.add_raw_property(handle(40), handle(7), 20);
+        other.add_raw_property(handle(40), handle(8), 30);
+        other.add_raw_property(handle(50), handle(6), 40);
+        other.blobs.push(9);
+
+        req.merge(other);
+        assert_eq!(req.objects, [handle(3), handle(40), handle(50)]);
+        assert_eq!(req.count_props_per_object, [1, 3, 1]);
+        assert_eq!(
+            req.props,
+            [handle(1), handle(6), handle(7), handle(8), handle(6)]
+        );
+        assert_eq!(req.values, [3, 1, 20, 30, 40]);
+        assert_eq!(req.blobs, [9]);
+    }
+
+    #[test]
+    fn merge_empty() {
+        let mut req = AtomicModeReq::new();
+        req.add_raw_property(handle(40), handle(6), 1);
+
+        req.merge(AtomicModeReq::new());
+        assert_eq!(req.objects, [handle(40)]);
+        assert_eq!(req.values, [1]);
+    }
+
+    #[test]
+    fn with_capacity_reuse() {
+        let device = blob_counter();
+        let mut req = AtomicModeReq::with_capacity(2, 4);
+        assert!(req.objects.capacity() >= 2);
+        assert!(req.count_props_per_object.capacity() >= 2);
+        assert!(req.props.capacity() >= 4);
+        assert!(req.values.capacity() >= 4);
+
+        for value in 0..4 {
+            req.add_raw_property(
+                handle(40 + value as u32 % 2),
+                handle(6 + value as u32),
+                value,
+            );
+        }
+        let props = req.props.as_ptr();
+
+        // Clearing keeps the allocations for the next frame
+        req.clear(&device).unwrap();
+        assert!(req.props.is_empty());
+        assert!(req.props.capacity() >= 4);
+        req.add_raw_property(handle(40), handle(6), 1);
+        assert_eq!(req.props.as_ptr(), props);
+        assert_eq!(req.count_props_per_object, [1]);
+    }
 }
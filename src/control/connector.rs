@@ -12,6 +12,11 @@ use drm_ffi as ffi;
 /// A handle to a connector
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -46,8 +51,22 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "connector::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about a connector
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) interface: Interface,
@@ -114,6 +133,75 @@ impl Info {
     pub fn subpixel(&self) -> SubPixel {
         self.subpixel
     }
+
+    /// Returns the mode the connected display prefers, usually its native
+    /// resolution, falling back to the first mode if none is marked as preferred.
+    pub fn preferred_mode(&self) -> Option<&control::Mode> {
+        self.modes
+            .iter()
+            .find(|mode| mode.mode_type().contains(control::ModeTypeFlags::PREFERRED))
+            .or_else(|| self.modes.first())
+    }
+
+    /// Returns the mode with the highest resolution, choosing the highest
+    /// refresh rate among modes of equal resolution.
+    ///
+    /// Earlier modes win ties, so preferred modes are picked over equivalent
+    /// ones as long as the kernel lists them first.
+    pub fn best_mode(&self) -> Option<&control::Mode> {
+        self.modes.iter().reduce(|best, mode| {
+            let key = |mode: &control::Mode| {
+                let (width, height) = mode.size();
+                (width as u32 * height as u32, mode.vrefresh())
+            };
+            if key(mode) > key(best) {
+                mode
+            } else {
+                best
+            }
+        })
+    }
+
+    /// Returns the number of modes this connector reports.
+    pub fn mode_count(&self) -> usize {
+        self.modes.len()
+    }
+
+    /// Compares this information to an earlier snapshot of the same connector.
+    ///
+    /// Compositors can use this after a hotplug event to decide whether the
+    /// outputs need to be laid out again: re-query the connector using
+    /// [`control::Device::get_connector`] and compare it to the previous result.
+    /// An empty set of changes means nothing relevant happened to this connector.
+    pub fn changes_since(&self, previous: &Info) -> Changes {
+        let mut changes = Changes::empty();
+        changes.set(Changes::STATE, self.connection != previous.connection);
+        changes.set(Changes::MODES, self.modes != previous.modes);
+        changes.set(Changes::SIZE, self.size != previous.size);
+        changes.set(
+            Changes::ENCODERS,
+            self.encoders != previous.encoders || self.curr_enc != previous.curr_enc,
+        );
+        changes.set(Changes::SUBPIXEL, self.subpixel != previous.subpixel);
+        changes
+    }
+}
+
+bitflags::bitflags! {
+    /// Aspects of a connector that changed, as returned by [`Info::changes_since`]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Changes: u32 {
+        /// The connection state changed
+        const STATE = 1 << 0;
+        /// The list of modes changed, in content or number
+        const MODES = 1 << 1;
+        /// The physical size of the display changed
+        const SIZE = 1 << 2;
+        /// The possible or current encoders changed
+        const ENCODERS = 1 << 3;
+        /// The subpixel order changed
+        const SUBPIXEL = 1 << 4;
+    }
 }
 
 /// A physical interface type.
@@ -121,6 +209,7 @@ impl Info {
 #[allow(clippy::upper_case_acronyms)]
 #[non_exhaustive]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Interface {
     Unknown,
     VGA,
@@ -173,6 +262,41 @@ impl Interface {
             Interface::USB => "USB",
         }
     }
+
+    /// Get interface from its name, as returned by [`Interface::as_str`]
+    pub fn from_name(name: &str) -> Option<Interface> {
+        const ALL: [Interface; 21] = [
+            Interface::Unknown,
+            Interface::VGA,
+            Interface::DVII,
+            Interface::DVID,
+            Interface::DVIA,
+            Interface::Composite,
+            Interface::SVideo,
+            Interface::LVDS,
+            Interface::Component,
+            Interface::NinePinDIN,
+            Interface::DisplayPort,
+            Interface::HDMIA,
+            Interface::HDMIB,
+            Interface::TV,
+            Interface::EmbeddedDisplayPort,
+            Interface::Virtual,
+            Interface::DSI,
+            Interface::DPI,
+            Interface::Writeback,
+            Interface::SPI,
+            Interface::USB,
+        ];
+        ALL.into_iter().find(|interface| interface.as_str() == name)
+    }
+}
+
+/// Parses a connector name like `HDMI-A-1`, as displayed by [`Info`], into its
+/// interface and interface ID.
+pub fn parse_name(name: &str) -> Option<(Interface, u32)> {
+    let (interface, id) = name.rsplit_once('-')?;
+    Some((Interface::from_name(interface)?, id.parse().ok()?))
 }
 
 impl From<u32> for Interface {
@@ -235,6 +359,7 @@ impl From<Interface> for u32 {
 /// The state of a connector.
 #[allow(missing_docs)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum State {
     Connected,
     Disconnected,
@@ -268,6 +393,7 @@ impl From<State> for u32 {
 /// Subpixel order of the connected sink
 #[non_exhaustive]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SubPixel {
     /// Unknown geometry
     Unknown,
@@ -301,3 +427,411 @@ impl SubPixel {
         }
     }
 }
+
+impl From<SubPixel> for u32 {
+    /// Converts back to the raw value. As the original value of
+    /// [`SubPixel::NotImplemented`] is lost, it is converted to the value of
+    /// [`SubPixel::Unknown`].
+    fn from(subpixel: SubPixel) -> Self {
+        match subpixel {
+            SubPixel::Unknown => 0,
+            SubPixel::HorizontalRgb => 1,
+            SubPixel::HorizontalBgr => 2,
+            SubPixel::VerticalRgb => 3,
+            SubPixel::VerticalBgr => 4,
+            SubPixel::None => 5,
+            SubPixel::NotImplemented => 0,
+        }
+    }
+}
+
+impl std::fmt::Display for SubPixel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // source: drm_connector.c, drm_get_subpixel_order_name
+        let name = match self {
+            SubPixel::Unknown => "Unknown",
+            SubPixel::HorizontalRgb => "Horizontal RGB",
+            SubPixel::HorizontalBgr => "Horizontal BGR",
+            SubPixel::VerticalRgb => "Vertical RGB",
+            SubPixel::VerticalBgr => "Vertical BGR",
+            SubPixel::None => "None",
+            SubPixel::NotImplemented => "Not implemented",
+        };
+        f.write_str(name)
+    }
+}
+
+/// How modes that do not match a panel's native resolution are scaled, as
+/// controlled by the `scaling mode` connector property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ScalingMode {
+    /// No scaling, the mode is sent to the panel as is
+    None,
+    /// Scale to the full panel size, ignoring the aspect ratio
+    Full,
+    /// Center the image without scaling it
+    Center,
+    /// Scale as large as possible while keeping the aspect ratio
+    FullAspect,
+}
+
+impl ScalingMode {
+    /// Get the name the kernel uses for this value of the `scaling mode` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, drm_scaling_mode_enum_list
+        match self {
+            ScalingMode::None => "None",
+            ScalingMode::Full => "Full",
+            ScalingMode::Center => "Center",
+            ScalingMode::FullAspect => "Full aspect",
+        }
+    }
+}
+
+/// Colorimetry signalled to the sink, as controlled by the `Colorspace`
+/// connector property.
+///
+/// Which values are available depends on the connector type.
+#[allow(clippy::upper_case_acronyms)]
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum Colorspace {
+    /// Driver default, usually sRGB for RGB output
+    Default,
+    /// ITU-R BT.601 YCbCr, as defined by SMPTE 170M
+    Smpte170mYcc,
+    /// ITU-R BT.709 YCbCr
+    BT709Ycc,
+    /// xvYCC based on ITU-R BT.601
+    XvYcc601,
+    /// xvYCC based on ITU-R BT.709
+    XvYcc709,
+    /// sYCC based on ITU-R BT.601
+    SYcc601,
+    /// opYCC based on ITU-R BT.601
+    OpYcc601,
+    /// opRGB, formerly AdobeRGB
+    OpRgb,
+    /// ITU-R BT.2020 constant luminance YCbCr
+    BT2020Cycc,
+    /// ITU-R BT.2020 RGB
+    BT2020Rgb,
+    /// ITU-R BT.2020 YCbCr
+    BT2020Ycc,
+    /// DCI-P3 with D65 white point
+    DciP3RgbD65,
+    /// DCI-P3 with theater white point
+    DciP3RgbTheater,
+    /// RGB wide gamut, fixed point
+    RgbWideFixed,
+    /// RGB wide gamut, floating point
+    RgbWideFloat,
+    /// ITU-R BT.601 YCbCr
+    BT601Ycc,
+}
+
+impl Colorspace {
+    /// Get the name the kernel uses for this value of the `Colorspace` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, colorspace_names
+        match self {
+            Colorspace::Default => "Default",
+            Colorspace::Smpte170mYcc => "SMPTE_170M_YCC",
+            Colorspace::BT709Ycc => "BT709_YCC",
+            Colorspace::XvYcc601 => "XVYCC_601",
+            Colorspace::XvYcc709 => "XVYCC_709",
+            Colorspace::SYcc601 => "SYCC_601",
+            Colorspace::OpYcc601 => "opYCC_601",
+            Colorspace::OpRgb => "opRGB",
+            Colorspace::BT2020Cycc => "BT2020_CYCC",
+            Colorspace::BT2020Rgb => "BT2020_RGB",
+            Colorspace::BT2020Ycc => "BT2020_YCC",
+            Colorspace::DciP3RgbD65 => "DCI-P3_RGB_D65",
+            Colorspace::DciP3RgbTheater => "DCI-P3_RGB_Theater",
+            Colorspace::RgbWideFixed => "RGB_WIDE_FIXED",
+            Colorspace::RgbWideFloat => "RGB_WIDE_FLOAT",
+            Colorspace::BT601Ycc => "BT601_YCC",
+        }
+    }
+}
+
+/// Power state of the sink, as controlled by the legacy `DPMS` connector property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum DpmsState {
+    /// Display is on
+    On,
+    /// Display is in standby
+    Standby,
+    /// Display is suspended
+    Suspend,
+    /// Display is off
+    Off,
+}
+
+impl DpmsState {
+    /// Get the name the kernel uses for this value of the `DPMS` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, drm_dpms_enum_list
+        match self {
+            DpmsState::On => "On",
+            DpmsState::Standby => "Standby",
+            DpmsState::Suspend => "Suspend",
+            DpmsState::Off => "Off",
+        }
+    }
+}
+
+/// RGB quantization range of the output, as controlled by the `Broadcast RGB`
+/// connector property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum BroadcastRgb {
+    /// Chosen by the driver, usually limited for CE modes and full otherwise
+    Automatic,
+    /// Full range, 0 to 255
+    Full,
+    /// Limited range, 16 to 235
+    Limited16_235,
+}
+
+impl BroadcastRgb {
+    /// Get the name the kernel uses for this value of the `Broadcast RGB` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, broadcast_rgb_names
+        match self {
+            BroadcastRgb::Automatic => "Automatic",
+            BroadcastRgb::Full => "Full",
+            BroadcastRgb::Limited16_235 => "Limited 16:235",
+        }
+    }
+}
+
+/// State of HDCP content protection, as controlled by the `Content Protection`
+/// connector property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ContentProtection {
+    /// Content protection is not requested
+    Undesired,
+    /// Content protection is requested, but not established yet
+    Desired,
+    /// Content protection is established. Set by the kernel only.
+    Enabled,
+}
+
+impl ContentProtection {
+    /// Get the name the kernel uses for this value of the `Content Protection` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, drm_cp_enum_list
+        match self {
+            ContentProtection::Undesired => "Undesired",
+            ContentProtection::Desired => "Desired",
+            ContentProtection::Enabled => "Enabled",
+        }
+    }
+
+    /// Get the value from its kernel name, as returned by [`ContentProtection::as_str`]
+    pub fn from_name(name: &str) -> Option<ContentProtection> {
+        [
+            ContentProtection::Undesired,
+            ContentProtection::Desired,
+            ContentProtection::Enabled,
+        ]
+        .into_iter()
+        .find(|value| value.as_str() == name)
+    }
+}
+
+/// Type of the displayed content, as signalled to the sink by the `content type`
+/// connector property.
+///
+/// HDMI sinks may use this to adjust their processing, e.g. to reduce latency
+/// for [`ContentType::Game`].
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ContentType {
+    /// No content type is signalled
+    NoData,
+    /// Graphics content
+    Graphics,
+    /// Photographic content
+    Photo,
+    /// Cinema content
+    Cinema,
+    /// Game content
+    Game,
+}
+
+impl ContentType {
+    /// Get the name the kernel uses for this value of the `content type` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_connector.c, drm_content_type_enum_list
+        match self {
+            ContentType::NoData => "No Data",
+            ContentType::Graphics => "Graphics",
+            ContentType::Photo => "Photo",
+            ContentType::Cinema => "Cinema",
+            ContentType::Game => "Game",
+        }
+    }
+
+    /// Get the value from its kernel name, as returned by [`ContentType::as_str`]
+    pub fn from_name(name: &str) -> Option<ContentType> {
+        [
+            ContentType::NoData,
+            ContentType::Graphics,
+            ContentType::Photo,
+            ContentType::Cinema,
+            ContentType::Game,
+        ]
+        .into_iter()
+        .find(|value| value.as_str() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "connector::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    #[test]
+    fn interface_round_trip() {
+        for raw in ffi::DRM_MODE_CONNECTOR_Unknown..=ffi::DRM_MODE_CONNECTOR_USB {
+            let interface = Interface::from(raw);
+            assert_eq!(u32::from(interface), raw);
+            assert_eq!(Interface::from_name(interface.as_str()), Some(interface));
+
+            let name = format!("{}-3", interface.as_str());
+            assert_eq!(parse_name(&name), Some((interface, 3)));
+        }
+    }
+
+    #[test]
+    fn parse_invalid_name() {
+        assert_eq!(Interface::from_name("HDMI"), None);
+        assert_eq!(parse_name("HDMI-A"), None);
+        assert_eq!(parse_name("HDMI-A-"), None);
+        assert_eq!(parse_name("HDMI-A-x"), None);
+        assert_eq!(parse_name("HDMI-C-1"), None);
+        assert_eq!(parse_name("1"), None);
+    }
+
+    #[test]
+    fn subpixel_round_trip() {
+        for raw in 0..=5 {
+            let subpixel = SubPixel::from_raw(raw);
+            assert_ne!(subpixel, SubPixel::NotImplemented);
+            assert_eq!(u32::from(subpixel), raw);
+        }
+        assert_eq!(SubPixel::from_raw(6), SubPixel::NotImplemented);
+        assert_eq!(
+            u32::from(SubPixel::NotImplemented),
+            u32::from(SubPixel::Unknown)
+        );
+    }
+
+    #[test]
+    fn subpixel_display() {
+        assert_eq!(SubPixel::HorizontalRgb.to_string(), "Horizontal RGB");
+        assert_eq!(SubPixel::VerticalBgr.to_string(), "Vertical BGR");
+        assert_eq!(SubPixel::None.to_string(), "None");
+    }
+
+    fn mode(size: (u16, u16), vrefresh: u32, mode_type: control::ModeTypeFlags) -> control::Mode {
+        control::Mode::new(
+            "",
+            0,
+            size.0,
+            0,
+            0,
+            0,
+            size.1,
+            0,
+            0,
+            0,
+            vrefresh,
+            control::ModeFlags::empty(),
+            mode_type,
+        )
+    }
+
+    fn info(modes: Vec<control::Mode>) -> Info {
+        Info {
+            handle: control::from_u32(1).unwrap(),
+            interface: Interface::HDMIA,
+            interface_id: 1,
+            connection: State::Connected,
+            size: None,
+            modes,
+            encoders: Vec::new(),
+            curr_enc: None,
+            subpixel: SubPixel::Unknown,
+        }
+    }
+
+    #[test]
+    fn preferred_mode() {
+        let driver = control::ModeTypeFlags::DRIVER;
+        let preferred = driver | control::ModeTypeFlags::PREFERRED;
+        let modes = vec![
+            mode((3840, 2160), 30, driver),
+            mode((2560, 1440), 60, preferred),
+            mode((1920, 1080), 60, driver),
+        ];
+        assert_eq!(info(modes.clone()).preferred_mode(), Some(&modes[1]));
+
+        // Without a preferred mode, the first mode is chosen
+        let modes = vec![
+            mode((1920, 1080), 60, driver),
+            mode((3840, 2160), 30, driver),
+        ];
+        assert_eq!(info(modes.clone()).preferred_mode(), Some(&modes[0]));
+
+        assert_eq!(info(Vec::new()).preferred_mode(), None);
+    }
+
+    #[test]
+    fn best_mode() {
+        let driver = control::ModeTypeFlags::DRIVER;
+        let preferred = driver | control::ModeTypeFlags::PREFERRED;
+        let modes = vec![
+            mode((2560, 1440), 60, preferred),
+            mode((3840, 2160), 30, driver),
+            mode((3840, 2160), 60, driver),
+            mode((3840, 2160), 60, driver | control::ModeTypeFlags::USERDEF),
+            mode((1920, 1080), 144, driver),
+        ];
+        // Highest resolution first, then refresh rate, earlier modes win ties
+        assert_eq!(info(modes.clone()).best_mode(), Some(&modes[2]));
+
+        assert_eq!(info(Vec::new()).best_mode(), None);
+    }
+
+    #[test]
+    fn changes_since() {
+        let driver = control::ModeTypeFlags::DRIVER;
+        let previous = info(vec![mode((1920, 1080), 60, driver)]);
+        assert_eq!(previous.changes_since(&previous), Changes::empty());
+
+        // Same connector, but an additional mode after a hotplug
+        let current = info(vec![
+            mode((1920, 1080), 60, driver),
+            mode((1280, 720), 60, driver),
+        ]);
+        assert_eq!(current.handle(), previous.handle());
+        assert_eq!(current.changes_since(&previous), Changes::MODES);
+
+        let disconnected = Info {
+            connection: State::Disconnected,
+            modes: Vec::new(),
+            ..previous.clone()
+        };
+        assert_eq!(
+            disconnected.changes_since(&previous),
+            Changes::STATE | Changes::MODES
+        );
+    }
+}
@@ -18,6 +18,11 @@ use drm_ffi as ffi;
 /// A handle to a specific CRTC
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -52,8 +57,22 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "crtc::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about a specific CRTC
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) position: (u32, u32),
@@ -94,3 +113,110 @@ impl Info {
         self.gamma_length
     }
 }
+
+/// Snapshot of the atomic state of a CRTC
+///
+/// Unlike [`Info`], which is based on the legacy modesetting API, this is
+/// built from the atomic properties of the CRTC, its planes and connectors.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct AtomicState {
+    pub(crate) handle: Handle,
+    pub(crate) active: bool,
+    pub(crate) mode: Option<control::Mode>,
+    pub(crate) planes: Vec<control::plane::Handle>,
+    pub(crate) connectors: Vec<control::connector::Handle>,
+}
+
+impl AtomicState {
+    /// Returns the handle to this CRTC.
+    pub fn handle(&self) -> Handle {
+        self.handle
+    }
+
+    /// Returns whether the CRTC is active, as set by its `ACTIVE` property.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the mode set by the `MODE_ID` property of the CRTC.
+    pub fn mode(&self) -> Option<control::Mode> {
+        self.mode
+    }
+
+    /// Returns the planes whose `CRTC_ID` property refers to this CRTC.
+    pub fn planes(&self) -> &[control::plane::Handle] {
+        &self.planes
+    }
+
+    /// Returns the connectors whose `CRTC_ID` property refers to this CRTC.
+    pub fn connectors(&self) -> &[control::connector::Handle] {
+        &self.connectors
+    }
+}
+
+/// A gamma ramp of a CRTC, consisting of one lookup table per color channel
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
+pub struct GammaRamp {
+    /// Lookup table of the red channel
+    pub red: Vec<u16>,
+    /// Lookup table of the green channel
+    pub green: Vec<u16>,
+    /// Lookup table of the blue channel
+    pub blue: Vec<u16>,
+}
+
+impl GammaRamp {
+    /// Creates a linear gamma ramp of `length` entries per channel, mapping
+    /// each channel to itself.
+    pub fn linear(length: usize) -> GammaRamp {
+        let table: Vec<u16> = (0..length)
+            .map(|i| (i * 0xffff / length.saturating_sub(1).max(1)) as u16)
+            .collect();
+        GammaRamp {
+            red: table.clone(),
+            green: table.clone(),
+            blue: table,
+        }
+    }
+
+    /// Returns the number of entries per channel, or [`None`] if the lookup
+    /// tables differ in length.
+    pub fn length(&self) -> Option<usize> {
+        let len = self.red.len();
+        if self.green.len() == len && self.blue.len() == len {
+            Some(len)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "crtc::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    #[test]
+    fn linear_gamma_ramp() {
+        let ramp = GammaRamp::linear(256);
+        assert_eq!(ramp.length(), Some(256));
+        assert!(ramp
+            .red
+            .iter()
+            .enumerate()
+            .all(|(i, &v)| v as usize == i * 257));
+        assert_eq!(ramp.green, ramp.red);
+        assert_eq!(ramp.blue, ramp.red);
+
+        assert_eq!(GammaRamp::linear(2).red, [0, 0xffff]);
+        assert_eq!(GammaRamp::linear(3).red, [0, 0x7fff, 0xffff]);
+        assert_eq!(GammaRamp::linear(1).red, [0]);
+        assert_eq!(GammaRamp::linear(0).length(), Some(0));
+    }
+}
@@ -6,7 +6,10 @@
 
 use crate::buffer;
 
+use rustix::io::Errno;
+
 use std::borrow::{Borrow, BorrowMut};
+use std::io;
 use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -15,14 +18,122 @@ pub struct DumbBuffer {
     pub(crate) size: (u32, u32),
     pub(crate) length: usize,
     pub(crate) format: buffer::DrmFourcc,
+    pub(crate) bpp: u32,
     pub(crate) pitch: u32,
     pub(crate) handle: buffer::Handle,
 }
 
+impl DumbBuffer {
+    /// The width and height of the buffer.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// The format of the buffer.
+    pub fn format(&self) -> buffer::DrmFourcc {
+        self.format
+    }
+
+    /// The pitch of the buffer.
+    pub fn pitch(&self) -> u32 {
+        self.pitch
+    }
+
+    /// The handle to the buffer.
+    pub fn handle(&self) -> buffer::Handle {
+        self.handle
+    }
+
+    /// Number of bytes holding the pixels of a single row.
+    pub(crate) fn row_len(&self) -> usize {
+        (self.size.0 as usize * self.bpp as usize + 7) / 8
+    }
+}
+
 /// Mapping of a [`DumbBuffer`]
 pub struct DumbMapping<'a> {
     pub(crate) _phantom: core::marker::PhantomData<&'a ()>,
     pub(crate) map: &'a mut [u8],
+    pub(crate) row_len: usize,
+}
+
+impl DumbMapping<'_> {
+    /// Returns an iterator over the rows of the mapped buffer.
+    ///
+    /// Each row starts `pitch` bytes after the previous one and is truncated
+    /// to the width of the buffer times its bytes per pixel. This skips the
+    /// padding of buffers whose pitch is larger than their rows.
+    ///
+    /// Fails with `EINVAL` if `pitch` is smaller than a row of the buffer.
+    pub fn rows_mut(&mut self, pitch: u32) -> io::Result<impl Iterator<Item = &mut [u8]>> {
+        let row_len = self.row_len;
+        self.rows(pitch, row_len)
+    }
+
+    fn rows(&mut self, pitch: u32, row_len: usize) -> io::Result<impl Iterator<Item = &mut [u8]>> {
+        if pitch == 0 || (pitch as usize) < row_len {
+            return Err(Errno::INVAL.into());
+        }
+
+        Ok(self
+            .map
+            .chunks_mut(pitch as usize)
+            .filter(move |row| row.len() >= row_len)
+            .map(move |row| &mut row[..row_len]))
+    }
+
+    /// Fills `size` pixels of the mapped buffer with a single color, given as
+    /// red, green, blue and alpha.
+    ///
+    /// The buffer is expected to use a 32 bit [`Argb8888`](buffer::DrmFourcc::Argb8888)
+    /// or [`Xrgb8888`](buffer::DrmFourcc::Xrgb8888) format, which store pixels in
+    /// blue, green, red, alpha byte order. Padding at the end of each row is left
+    /// untouched.
+    ///
+    /// Fails with `EINVAL` if `pitch` is smaller than `size.0 * 4`.
+    pub fn fill(&mut self, color: [u8; 4], pitch: u32, size: (u32, u32)) -> io::Result<()> {
+        let [r, g, b, a] = color;
+        let rows = self.rows(pitch, size.0 as usize * 4)?;
+        for row in rows.take(size.1 as usize) {
+            for px in row.chunks_exact_mut(4) {
+                px.copy_from_slice(&[b, g, r, a]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies tightly packed pixels in red, green, blue, alpha byte order, such
+    /// as an RGBA image, into the mapped buffer.
+    ///
+    /// Like [`DumbMapping::fill`], this expects a 32 bit
+    /// [`Argb8888`](buffer::DrmFourcc::Argb8888) or
+    /// [`Xrgb8888`](buffer::DrmFourcc::Xrgb8888) buffer and respects its pitch.
+    ///
+    /// Fails with `EINVAL` if `pitch` is smaller than `width * 4` or `pixels`
+    /// holds less than `width * height` pixels.
+    pub fn copy_from_rgba(
+        &mut self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+        pitch: u32,
+    ) -> io::Result<()> {
+        let row_len = width as usize * 4;
+        if pixels.len() < row_len * height as usize {
+            return Err(Errno::INVAL.into());
+        }
+
+        let rows = self.rows(pitch, row_len)?;
+        if row_len == 0 {
+            return Ok(());
+        }
+        for (src_row, dst_row) in pixels.chunks_exact(row_len).zip(rows.take(height as usize)) {
+            for (src, dst) in src_row.chunks_exact(4).zip(dst_row.chunks_exact_mut(4)) {
+                dst.copy_from_slice(&[src[2], src[1], src[0], src[3]]);
+            }
+        }
+        Ok(())
+    }
 }
 
 impl AsRef<[u8]> for DumbMapping<'_> {
@@ -86,3 +197,122 @@ impl buffer::Buffer for DumbBuffer {
         self.handle
     }
 }
+
+/// Dumb buffers consist of a single linear plane without a modifier.
+impl buffer::PlanarBuffer for DumbBuffer {
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    fn format(&self) -> buffer::DrmFourcc {
+        self.format
+    }
+    fn modifier(&self) -> Option<buffer::DrmModifier> {
+        None
+    }
+    fn pitches(&self) -> [u32; 4] {
+        [self.pitch, 0, 0, 0]
+    }
+    fn handles(&self) -> [Option<buffer::Handle>; 4] {
+        [Some(self.handle), None, None, None]
+    }
+    fn offsets(&self) -> [u32; 4] {
+        [0, 0, 0, 0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DumbMapping;
+    use rustix::io::Errno;
+
+    /// Maps anonymous memory, as it is unmapped again on drop
+    fn anonymous_mapping(len: usize, row_len: usize) -> DumbMapping<'static> {
+        use rustix::mm;
+        let map = unsafe {
+            mm::mmap_anonymous(
+                std::ptr::null_mut(),
+                len,
+                mm::ProtFlags::READ | mm::ProtFlags::WRITE,
+                mm::MapFlags::PRIVATE,
+            )
+            .unwrap()
+        };
+        DumbMapping {
+            _phantom: std::marker::PhantomData,
+            map: unsafe { std::slice::from_raw_parts_mut(map as *mut u8, len) },
+            row_len,
+        }
+    }
+
+    #[test]
+    fn rows_mut_skips_padding() {
+        // 3 rows of 2 pixels at 4 bytes each, padded to 12 bytes
+        let mut mapping = anonymous_mapping(36, 8);
+        let mut count = 0;
+        for row in mapping.rows_mut(12).unwrap() {
+            assert_eq!(row.len(), 8);
+            row.fill(0xff);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        for row in mapping.chunks(12) {
+            assert_eq!(&row[..8], &[0xff; 8]);
+            assert_eq!(&row[8..], &[0; 4]);
+        }
+    }
+
+    #[test]
+    fn rows_mut_invalid_pitch() {
+        let mut mapping = anonymous_mapping(36, 8);
+        assert!(mapping.rows_mut(0).is_err());
+        assert!(mapping.rows_mut(4).is_err());
+        assert_eq!(mapping.rows_mut(8).unwrap().count(), 4);
+    }
+
+    #[test]
+    fn fill_skips_padding() {
+        let mut mapping = anonymous_mapping(24, 8);
+        mapping.fill([1, 2, 3, 4], 12, (2, 2)).unwrap();
+        assert_eq!(
+            mapping.as_ref(),
+            [3, 2, 1, 4, 3, 2, 1, 4, 0, 0, 0, 0, 3, 2, 1, 4, 3, 2, 1, 4, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn fill_invalid_pitch() {
+        let mut mapping = anonymous_mapping(24, 8);
+        let err = mapping.fill([1, 2, 3, 4], 4, (2, 2)).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+        assert!(mapping.as_ref().iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn copy_from_rgba_swizzles() {
+        let mut mapping = anonymous_mapping(24, 8);
+        let pixels = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        mapping.copy_from_rgba(&pixels, 2, 2, 12).unwrap();
+        assert_eq!(
+            mapping.as_ref(),
+            [3, 2, 1, 4, 7, 6, 5, 8, 0, 0, 0, 0, 11, 10, 9, 12, 15, 14, 13, 16, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn copy_from_rgba_invalid() {
+        let mut mapping = anonymous_mapping(24, 8);
+        let pixels = [0xff; 16];
+
+        // Pitch smaller than a row of pixels
+        let err = mapping.copy_from_rgba(&pixels, 2, 2, 4).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+
+        // Less pixels than width * height
+        let err = mapping.copy_from_rgba(&pixels, 2, 3, 12).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+
+        assert!(mapping.as_ref().iter().all(|&b| b == 0));
+        mapping.copy_from_rgba(&[], 0, 2, 12).unwrap();
+    }
+}
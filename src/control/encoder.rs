@@ -9,6 +9,11 @@ use drm_ffi as ffi;
 /// A handle to an encoder
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -43,8 +48,22 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "encoder::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about an encoder
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) enc_type: Kind,
@@ -93,6 +112,7 @@ impl Info {
 #[allow(missing_docs)]
 #[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
     None,
     DAC,
@@ -137,3 +157,15 @@ impl From<Kind> for u32 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "encoder::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+}
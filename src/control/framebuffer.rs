@@ -10,6 +10,11 @@ use drm_fourcc::{DrmFourcc, DrmModifier};
 /// A handle to a framebuffer
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -44,6 +49,19 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "framebuffer::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about a framebuffer
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct Info {
@@ -88,6 +106,9 @@ impl Info {
     }
 
     /// Returns the buffer handle of this framebuffer.
+    ///
+    /// [`None`] if the kernel withheld the handle because the caller is not
+    /// the DRM Master.
     pub fn buffer(&self) -> Option<buffer::Handle> {
         self.buffer
     }
@@ -104,6 +125,7 @@ pub struct PlanarInfo {
     pub(crate) pitches: [u32; 4],
     pub(crate) offsets: [u32; 4],
     pub(crate) modifier: Option<DrmModifier>,
+    pub(crate) modifiers: [Option<DrmModifier>; 4],
 }
 
 impl PlanarInfo {
@@ -143,7 +165,75 @@ impl PlanarInfo {
     }
 
     /// Returns the modifier of this framebuffer.
+    ///
+    /// This is the modifier of the first plane, see [`PlanarInfo::modifiers`].
     pub fn modifier(&self) -> Option<DrmModifier> {
         self.modifier
     }
+
+    /// Returns the modifier of each plane of this framebuffer.
+    ///
+    /// Planes not used by the pixel format are [`None`], as are all planes if
+    /// the framebuffer was created without modifiers.
+    pub fn modifiers(&self) -> [Option<DrmModifier>; 4] {
+        self.modifiers
+    }
+
+    /// Returns the size in bytes of each plane of this framebuffer, computed
+    /// from its pitch and height.
+    ///
+    /// Planes not used by the pixel format are [`None`], as are all planes of
+    /// formats unknown to [`buffer::FormatInfo`]. The sizes are only exact for
+    /// linear layouts, tiled modifiers may require additional padding.
+    pub fn plane_sizes(&self) -> [Option<u64>; 4] {
+        let mut sizes = [None; 4];
+        if let Some(info) = buffer::FormatInfo::from_format(self.pixel_format) {
+            for (plane, size) in sizes.iter_mut().enumerate().take(info.num_planes()) {
+                let height = info.plane_height(self.size.1, plane);
+                *size = Some(self.pitches[plane] as u64 * height as u64);
+            }
+        }
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "framebuffer::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    fn planar_info(pixel_format: DrmFourcc, size: (u32, u32), pitches: [u32; 4]) -> PlanarInfo {
+        PlanarInfo {
+            handle: control::from_u32(1).unwrap(),
+            size,
+            pixel_format,
+            flags: control::FbCmd2Flags::empty(),
+            buffers: [None; 4],
+            pitches,
+            offsets: [0; 4],
+            modifier: None,
+            modifiers: [None; 4],
+        }
+    }
+
+    #[test]
+    fn nv12_plane_sizes() {
+        let info = planar_info(DrmFourcc::Nv12, (1920, 1080), [1920, 1920, 0, 0]);
+        assert_eq!(
+            info.plane_sizes(),
+            [Some(1920 * 1080), Some(1920 * 540), None, None]
+        );
+    }
+
+    #[test]
+    fn packed_plane_sizes() {
+        let info = planar_info(DrmFourcc::Xrgb8888, (1920, 1080), [7680, 0, 0, 0]);
+        assert_eq!(info.plane_sizes(), [Some(7680 * 1080), None, None, None]);
+    }
 }
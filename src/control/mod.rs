@@ -6,20 +6,20 @@
 //! exposing the following resource types:
 //!
 //! * FrameBuffer - Specific to an individual process, these wrap around generic
-//! GPU buffers so that they can be attached to a Plane.
+//!   GPU buffers so that they can be attached to a Plane.
 //!
 //! * Planes - Dedicated memory objects which contain a buffer that can then be
-//! scanned out by a CRTC. There exist a few different types of planes depending
-//! on the use case.
+//!   scanned out by a CRTC. There exist a few different types of planes depending
+//!   on the use case.
 //!
 //! * CRTC - Scanout engines that read pixel data from a Plane and sends it to
-//! a Connector. Each CRTC has at least one Primary Plane.
+//!   a Connector. Each CRTC has at least one Primary Plane.
 //!
 //! * Connector - Represents the physical output, such as a DisplayPort or
-//! VGA connector.
+//!   VGA connector.
 //!
 //! * Encoder - Encodes pixel data from a CRTC into something a Connector can
-//! understand.
+//!   understand.
 //!
 //! Further details on each resource can be found in their respective modules.
 //!
@@ -32,6 +32,7 @@ use drm_ffi as ffi;
 use drm_fourcc::{DrmFourcc, DrmModifier, UnrecognizedFourcc};
 
 use bytemuck::allocation::TransparentWrapperAlloc;
+use rustix::event::{PollFd, PollFlags};
 use rustix::io::Errno;
 
 pub mod atomic;
@@ -42,6 +43,7 @@ pub mod encoder;
 pub mod framebuffer;
 pub mod plane;
 pub mod syncobj;
+pub mod tile;
 
 pub mod property;
 
@@ -59,7 +61,9 @@ use std::iter::Zip;
 use std::mem;
 use std::ops::RangeBounds;
 use std::os::unix::io::{AsFd, BorrowedFd, FromRawFd, OwnedFd, RawFd};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use core::num::NonZeroU32;
 
@@ -123,6 +127,87 @@ impl From<UnrecognizedFourcc> for GetPlanarFramebufferError {
     }
 }
 
+/// Error from [`Device::atomic_commit_classified`]
+///
+/// Classifies the error codes commonly returned by atomic commits, to allow
+/// callers to decide between retrying, falling back to another configuration
+/// or giving up. Every variant holds the original [`io::Error`].
+#[derive(Debug)]
+pub enum AtomicCommitError {
+    /// The requested configuration is invalid or not supported by the driver (`EINVAL`)
+    InvalidConfig(io::Error),
+    /// A previous commit affecting the same objects is still pending (`EBUSY`)
+    Busy(io::Error),
+    /// The caller is not allowed to commit, e.g. because it is not the DRM Master (`EACCES`, `EPERM`)
+    PermissionDenied(io::Error),
+    /// A property value, such as plane source coordinates, is out of range (`ERANGE`)
+    OutOfRange(io::Error),
+    /// The hardware lacks the resources, such as bandwidth, for the configuration (`ENOSPC`)
+    InsufficientResources(io::Error),
+    /// Any other error
+    Other(io::Error),
+}
+
+impl AtomicCommitError {
+    /// Returns the underlying IO error
+    pub fn io_error(&self) -> &io::Error {
+        match self {
+            Self::InvalidConfig(err)
+            | Self::Busy(err)
+            | Self::PermissionDenied(err)
+            | Self::OutOfRange(err)
+            | Self::InsufficientResources(err)
+            | Self::Other(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for AtomicCommitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Self::InvalidConfig(_) => "invalid configuration",
+            Self::Busy(_) => "device busy",
+            Self::PermissionDenied(_) => "permission denied",
+            Self::OutOfRange(_) => "value out of range",
+            Self::InsufficientResources(_) => "insufficient resources",
+            Self::Other(_) => "atomic commit failed",
+        };
+        write!(f, "{}: {}", reason, self.io_error())
+    }
+}
+
+impl error::Error for AtomicCommitError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(self.io_error())
+    }
+}
+
+impl From<io::Error> for AtomicCommitError {
+    fn from(err: io::Error) -> Self {
+        match err.raw_os_error().map(Errno::from_raw_os_error) {
+            Some(Errno::INVAL) => Self::InvalidConfig(err),
+            Some(Errno::BUSY) => Self::Busy(err),
+            Some(Errno::ACCESS) | Some(Errno::PERM) => Self::PermissionDenied(err),
+            Some(Errno::RANGE) => Self::OutOfRange(err),
+            Some(Errno::NOSPC) => Self::InsufficientResources(err),
+            _ => Self::Other(err),
+        }
+    }
+}
+
+impl From<AtomicCommitError> for io::Error {
+    fn from(err: AtomicCommitError) -> Self {
+        match err {
+            AtomicCommitError::InvalidConfig(err)
+            | AtomicCommitError::Busy(err)
+            | AtomicCommitError::PermissionDenied(err)
+            | AtomicCommitError::OutOfRange(err)
+            | AtomicCommitError::InsufficientResources(err)
+            | AtomicCommitError::Other(err) => err,
+        }
+    }
+}
+
 /// This trait should be implemented by any object that acts as a DRM device and
 /// provides modesetting functionality.
 ///
@@ -136,8 +221,53 @@ impl From<UnrecognizedFourcc> for GetPlanarFramebufferError {
 /// /// Assuming the [`Card`] wrapper already implements [`drm::Device`]
 /// impl ControlDevice for Card {}
 /// ```
+///
+/// # Threading
+///
+/// All methods take the device by shared reference and are safe to call from
+/// several threads at once, provided the implementing type is [`Sync`]. The
+/// kernel serializes the requests on the file descriptor. In particular, one
+/// thread may block in [`Device::receive_events`] while another thread commits
+/// the state producing those events. [`Events`] owns a copy of the data read,
+/// so it can be sent to other threads for processing.
+///
+/// Event reads are not synchronized with each other, so only a single thread
+/// should read events at a time, otherwise events are split between readers.
+///
+/// ```no_run
+/// use drm::control::{atomic, AtomicCommitFlags, Device as ControlDevice, Event};
+/// use std::os::unix::io::{AsFd, BorrowedFd};
+///
+/// struct Card(std::fs::File);
+///
+/// impl AsFd for Card {
+///     fn as_fd(&self) -> BorrowedFd<'_> {
+///         self.0.as_fd()
+///     }
+/// }
+///
+/// impl drm::Device for Card {}
+/// impl ControlDevice for Card {}
+///
+/// let card = Card(std::fs::File::open("/dev/dri/card0").unwrap());
+/// std::thread::scope(|s| {
+///     s.spawn(|| {
+///         for event in card.receive_events().unwrap() {
+///             if let Event::PageFlip(event) = event {
+///                 println!("flip completed on {:?}", event.crtc);
+///             }
+///         }
+///     });
+///     let req = atomic::AtomicModeReq::new();
+///     let flags = AtomicCommitFlags::PAGE_FLIP_EVENT | AtomicCommitFlags::NONBLOCK;
+///     card.atomic_commit(flags, req).unwrap();
+/// });
+/// ```
 pub trait Device: super::Device {
     /// Gets the set of resource handles that this device currently controls
+    ///
+    /// Framebuffers are only listed if they were created through this open
+    /// file, see [`ResourceHandles::framebuffers`].
     fn resource_handles(&self) -> io::Result<ResourceHandles> {
         let mut fbs = Vec::new();
         let mut crtcs = Vec::new();
@@ -166,6 +296,39 @@ pub trait Device: super::Device {
         Ok(res)
     }
 
+    /// Updates a set of [`ResourceHandles`] in place, reusing its allocations.
+    ///
+    /// Unlike [`Device::resource_handles`], which queries the number of
+    /// resources before fetching them, this usually needs a single ioctl once
+    /// `res` has been filled before. This makes it cheaper to refresh the
+    /// resources, e.g. on every hotplug event. On error, `res` is left empty.
+    fn resource_handles_into(&self, res: &mut ResourceHandles) -> io::Result<()> {
+        // SAFETY: all handles are repr(transparent) over NonZeroU32
+        let mut fbs: Vec<u32> = unsafe { transmute_vec(mem::take(&mut res.fbs)) };
+        let mut crtcs: Vec<u32> = unsafe { transmute_vec(mem::take(&mut res.crtcs)) };
+        let mut connectors: Vec<u32> = unsafe { transmute_vec(mem::take(&mut res.connectors)) };
+        let mut encoders: Vec<u32> = unsafe { transmute_vec(mem::take(&mut res.encoders)) };
+
+        let ffi_res = ffi::mode::get_resources_into(
+            self.as_fd(),
+            &mut fbs,
+            &mut crtcs,
+            &mut connectors,
+            &mut encoders,
+        )?;
+
+        unsafe {
+            res.fbs = transmute_vec_from_u32(fbs);
+            res.crtcs = transmute_vec_from_u32(crtcs);
+            res.connectors = transmute_vec_from_u32(connectors);
+            res.encoders = transmute_vec_from_u32(encoders);
+        }
+        res.width = (ffi_res.min_width, ffi_res.max_width);
+        res.height = (ffi_res.min_height, ffi_res.max_height);
+
+        Ok(())
+    }
+
     /// Gets the set of plane handles that this device currently has
     fn plane_handles(&self) -> io::Result<Vec<plane::Handle>> {
         let mut planes = Vec::new();
@@ -173,6 +336,48 @@ pub trait Device: super::Device {
         Ok(unsafe { transmute_vec_from_u32(planes) })
     }
 
+    /// Returns the type of the object with the given id.
+    ///
+    /// The type is determined by searching the resource lists of the device,
+    /// so primary and cursor planes are only found if
+    /// [`ClientCapability::UniversalPlanes`](crate::ClientCapability::UniversalPlanes)
+    /// is enabled. Properties and blobs are probed directly. If no object with
+    /// this id exists, `ENOENT` is returned.
+    fn object_type(&self, id: RawResourceHandle) -> io::Result<ObjectType> {
+        let res = self.resource_handles()?;
+        let raw = u32::from(id);
+        if res.crtcs.iter().any(|&handle| u32::from(handle) == raw) {
+            return Ok(ObjectType::CRTC);
+        }
+        if res
+            .connectors
+            .iter()
+            .any(|&handle| u32::from(handle) == raw)
+        {
+            return Ok(ObjectType::Connector);
+        }
+        if res.encoders.iter().any(|&handle| u32::from(handle) == raw) {
+            return Ok(ObjectType::Encoder);
+        }
+        if res.fbs.iter().any(|&handle| u32::from(handle) == raw) {
+            return Ok(ObjectType::Framebuffer);
+        }
+        if self
+            .plane_handles()?
+            .iter()
+            .any(|&handle| u32::from(handle) == raw)
+        {
+            return Ok(ObjectType::Plane);
+        }
+        if self.get_property(property::Handle::from(id)).is_ok() {
+            return Ok(ObjectType::Property);
+        }
+        if ffi::mode::get_property_blob(self.as_fd(), raw, None).is_ok() {
+            return Ok(ObjectType::Blob);
+        }
+        Err(Errno::NOENT.into())
+    }
+
     /// Returns information about a specific connector
     ///
     /// ## Force-probing
@@ -184,6 +389,10 @@ pub trait Device: super::Device {
     /// - User needs to force-probe connectors to ensure their metadata is up-to-date at startup and after receiving a hot-plug event.
     /// - User may perform a forced-probe when the user explicitly requests it.
     /// - User shouldn’t perform a forced-probe in other situations.
+    ///
+    /// The kernel silently ignores `force_probe` for clients that are not the
+    /// DRM master, returning the cached state of the connector instead. Use
+    /// [`Device::get_connector_force_probed`] to treat this as an error.
     fn get_connector(
         &self,
         handle: connector::Handle,
@@ -214,13 +423,39 @@ pub trait Device: super::Device {
             },
             modes: Mode::wrap_vec(modes),
             encoders: unsafe { transmute_vec_from_u32(encoders) },
-            curr_enc: unsafe { mem::transmute(ffi_info.encoder_id) },
+            curr_enc: unsafe {
+                mem::transmute::<u32, Option<encoder::Handle>>(ffi_info.encoder_id)
+            },
             subpixel: connector::SubPixel::from_raw(ffi_info.subpixel),
         };
 
         Ok(connector)
     }
 
+    /// Returns the connection state of a connector, without probing it.
+    ///
+    /// Neither modes nor encoders are transferred, which makes this cheaper
+    /// than [`Device::get_connector`] when checking many connectors, e.g. after
+    /// a hotplug event.
+    fn connector_state(&self, handle: connector::Handle) -> io::Result<connector::State> {
+        let ffi_info =
+            ffi::mode::get_connector(self.as_fd(), handle.into(), None, None, None, None, false)?;
+
+        Ok(connector::State::from(ffi_info.connection))
+    }
+
+    /// Returns information about a specific connector after force-probing it
+    ///
+    /// Fails with `EACCES` if this process is not the DRM Master, as the kernel
+    /// would skip the probe. See [`Device::get_connector`] for when probing is
+    /// appropriate.
+    fn get_connector_force_probed(&self, handle: connector::Handle) -> io::Result<connector::Info> {
+        if !self.is_master()? {
+            return Err(Errno::ACCESS.into());
+        }
+        self.get_connector(handle, true)
+    }
+
     /// Returns information about a specific encoder
     fn get_encoder(&self, handle: encoder::Handle) -> io::Result<encoder::Info> {
         let info = ffi::mode::get_encoder(self.as_fd(), handle.into())?;
@@ -254,7 +489,60 @@ pub trait Device: super::Device {
         Ok(crtc)
     }
 
+    /// Returns a snapshot of the atomic state of a crtc.
+    ///
+    /// On drivers supporting atomic modesetting, this reflects the current
+    /// configuration more accurately than [`Device::get_crtc`]. Requires the
+    /// [`Atomic`](crate::ClientCapability::Atomic) client capability, `ENOENT`
+    /// is returned otherwise.
+    fn get_crtc_atomic_state(&self, handle: crtc::Handle) -> io::Result<crtc::AtomicState> {
+        let (_, active) = find_property(self, handle, "ACTIVE")?.ok_or(Errno::NOENT)?;
+        let (_, mode_id) = find_property(self, handle, "MODE_ID")?.ok_or(Errno::NOENT)?;
+
+        let mode = if mode_id != 0 {
+            let data = self.get_property_blob(mode_id)?;
+            if data.len() < mem::size_of::<ffi::drm_mode_modeinfo>() {
+                return Err(Errno::INVAL.into());
+            }
+            let info =
+                unsafe { std::ptr::read_unaligned(data.as_ptr() as *const ffi::drm_mode_modeinfo) };
+            Some(Mode::from(info))
+        } else {
+            None
+        };
+
+        let crtc_id = u32::from(handle) as property::RawValue;
+        let mut planes = Vec::new();
+        for plane in self.plane_handles()? {
+            if let Some((_, value)) = find_property(self, plane, "CRTC_ID")? {
+                if value == crtc_id {
+                    planes.push(plane);
+                }
+            }
+        }
+        let mut connectors = Vec::new();
+        for &connector in self.resource_handles()?.connectors() {
+            if let Some((_, value)) = find_property(self, connector, "CRTC_ID")? {
+                if value == crtc_id {
+                    connectors.push(connector);
+                }
+            }
+        }
+
+        Ok(crtc::AtomicState {
+            handle,
+            active: active != 0,
+            mode,
+            planes,
+            connectors,
+        })
+    }
+
     /// Set CRTC state
+    ///
+    /// Setting a mode requires at least one connector, and connectors require
+    /// both a framebuffer and a mode. Other combinations are rejected with
+    /// `EINVAL` before issuing the ioctl. Disable a crtc by passing neither.
     fn set_crtc(
         &self,
         handle: crtc::Handle,
@@ -263,6 +551,12 @@ pub trait Device: super::Device {
         conns: &[connector::Handle],
         mode: Option<Mode>,
     ) -> io::Result<()> {
+        if (mode.is_some() && conns.is_empty())
+            || (!conns.is_empty() && (framebuffer.is_none() || mode.is_none()))
+        {
+            return Err(Errno::INVAL.into());
+        }
+
         let _info = ffi::mode::set_crtc(
             self.as_fd(),
             handle.into(),
@@ -276,7 +570,80 @@ pub trait Device: super::Device {
         Ok(())
     }
 
+    /// Disables a crtc using the legacy modesetting API.
+    fn disable_crtc(&self, handle: crtc::Handle) -> io::Result<()> {
+        self.set_crtc(handle, None, (0, 0), &[], None)
+    }
+
+    /// Disables a crtc using an atomic commit.
+    ///
+    /// Besides deactivating the crtc and clearing its mode, the connectors and
+    /// planes bound to the crtc are detached from it. Requires the
+    /// [`Atomic`](crate::ClientCapability::Atomic) client capability.
+    fn disable_crtc_atomic(&self, handle: crtc::Handle) -> io::Result<()> {
+        let crtc_id = u32::from(handle) as property::RawValue;
+        let mut req = atomic::AtomicModeReq::new();
+        add_property_by_name(self, &mut req, handle, "ACTIVE", 0)?;
+        add_property_by_name(self, &mut req, handle, "MODE_ID", 0)?;
+
+        for &connector in self.resource_handles()?.connectors() {
+            if let Some((_, value)) = find_property(self, connector, "CRTC_ID")? {
+                if value == crtc_id {
+                    add_property_by_name(self, &mut req, connector, "CRTC_ID", 0)?;
+                }
+            }
+        }
+
+        for plane in self.plane_handles()? {
+            if let Some((_, value)) = find_property(self, plane, "CRTC_ID")? {
+                if value == crtc_id {
+                    add_property_by_name(self, &mut req, plane, "CRTC_ID", 0)?;
+                    add_property_by_name(self, &mut req, plane, "FB_ID", 0)?;
+                }
+            }
+        }
+
+        self.atomic_commit(AtomicCommitFlags::ALLOW_MODESET, req)
+    }
+
+    /// Returns the connectors currently routed to the given crtc.
+    ///
+    /// The routing is determined by the current encoder of each connector and
+    /// the current crtc of that encoder, as set by legacy modesetting or atomic
+    /// commits. Connectors are not probed.
+    fn crtc_connectors(&self, crtc: crtc::Handle) -> io::Result<Vec<connector::Handle>> {
+        let resources = self.resource_handles()?;
+        let mut encoders = Vec::new();
+        for &handle in resources.encoders() {
+            if self.get_encoder(handle)?.crtc() == Some(crtc) {
+                encoders.push(handle);
+            }
+        }
+
+        let mut connectors = Vec::new();
+        if encoders.is_empty() {
+            return Ok(connectors);
+        }
+        for &handle in resources.connectors() {
+            let info = self.get_connector(handle, false)?;
+            if info
+                .current_encoder()
+                .map_or(false, |encoder| encoders.contains(&encoder))
+            {
+                connectors.push(handle);
+            }
+        }
+        Ok(connectors)
+    }
+
     /// Returns information about a specific framebuffer
+    ///
+    /// # Notes
+    ///
+    /// The kernel only returns the GEM handle backing the framebuffer to the
+    /// DRM Master or processes with `CAP_SYS_ADMIN`. For all other callers
+    /// [`framebuffer::Info::buffer`] is [`None`]. Use
+    /// [`Device::get_framebuffer_checked`] to treat this as an error instead.
     fn get_framebuffer(&self, handle: framebuffer::Handle) -> io::Result<framebuffer::Info> {
         let info = ffi::mode::get_framebuffer(self.as_fd(), handle.into())?;
 
@@ -292,6 +659,21 @@ pub trait Device: super::Device {
         Ok(fb)
     }
 
+    /// Returns information about a specific framebuffer, including its GEM handle
+    ///
+    /// Fails with `EACCES` if the kernel withheld the GEM handle, because this
+    /// process is neither the DRM Master nor has `CAP_SYS_ADMIN`.
+    fn get_framebuffer_checked(
+        &self,
+        handle: framebuffer::Handle,
+    ) -> io::Result<framebuffer::Info> {
+        let fb = self.get_framebuffer(handle)?;
+        if fb.buffer.is_none() {
+            return Err(Errno::ACCESS.into());
+        }
+        Ok(fb)
+    }
+
     /// Returns information about a specific framebuffer (with modifiers)
     fn get_planar_framebuffer(
         &self,
@@ -306,6 +688,17 @@ pub trait Device: super::Device {
             .contains(FbCmd2Flags::MODIFIERS)
             .then(|| DrmModifier::from(info.modifier[0]));
 
+        // Buffer handles may be withheld, so tell used planes apart by format
+        let num_planes = buffer::FormatInfo::from_format(pixel_format)
+            .map(|format_info| format_info.num_planes());
+        let mut modifiers = [None; 4];
+        for (plane, plane_modifier) in modifiers.iter_mut().enumerate() {
+            let used = num_planes.map_or(info.pitches[plane] != 0, |num| plane < num);
+            if used && modifier.is_some() {
+                *plane_modifier = Some(DrmModifier::from(info.modifier[plane]));
+            }
+        }
+
         let fb = framebuffer::PlanarInfo {
             handle,
             size: (info.width, info.height),
@@ -315,6 +708,7 @@ pub trait Device: super::Device {
             pitches: info.pitches,
             offsets: info.offsets,
             modifier,
+            modifiers,
         };
 
         Ok(fb)
@@ -327,6 +721,39 @@ pub trait Device: super::Device {
         depth: u32,
         bpp: u32,
     ) -> io::Result<framebuffer::Handle>
+    where
+        B: buffer::Buffer + ?Sized,
+    {
+        self.add_framebuffer_with_pitch(buffer, depth, bpp, buffer.pitch())
+    }
+
+    /// Add a new framebuffer, deriving depth and bpp from the format of the buffer.
+    ///
+    /// The legacy framebuffer API only describes formats by depth and bpp, so
+    /// this is limited to the formats the kernel derives from such a pair:
+    /// `C8`, `XRGB1555`, `RGB565`, `RGB888`, `XRGB8888`, `XRGB2101010` and
+    /// `ARGB8888`. Fails with `EINVAL` for any other format, which needs to be
+    /// added with [`Device::add_planar_framebuffer`] instead.
+    fn add_framebuffer_auto<B>(&self, buffer: &B) -> io::Result<framebuffer::Handle>
+    where
+        B: buffer::Buffer + ?Sized,
+    {
+        let (depth, bpp) = legacy_depth_bpp(buffer.format())?;
+        self.add_framebuffer(buffer, depth, bpp)
+    }
+
+    /// Add a new framebuffer like [`Device::add_framebuffer`], using `pitch`
+    /// instead of the pitch reported by the buffer.
+    ///
+    /// This is useful for buffers padded or aligned beyond the pitch required
+    /// for scanout.
+    fn add_framebuffer_with_pitch<B>(
+        &self,
+        buffer: &B,
+        depth: u32,
+        bpp: u32,
+        pitch: u32,
+    ) -> io::Result<framebuffer::Handle>
     where
         B: buffer::Buffer + ?Sized,
     {
@@ -335,7 +762,7 @@ pub trait Device: super::Device {
             self.as_fd(),
             w,
             h,
-            buffer.pitch(),
+            pitch,
             bpp,
             depth,
             buffer.handle().into(),
@@ -345,6 +772,9 @@ pub trait Device: super::Device {
     }
 
     /// Add framebuffer (with modifiers)
+    ///
+    /// [`FbCmd2Flags::MODIFIERS`] has to be set if and only if the buffer has
+    /// a valid modifier, otherwise `EINVAL` is returned.
     fn add_planar_framebuffer<B>(
         &self,
         planar_buffer: &B,
@@ -357,7 +787,9 @@ pub trait Device: super::Device {
             .modifier()
             .filter(|modifier| !matches!(modifier, DrmModifier::Invalid));
         let has_modifier = flags.contains(FbCmd2Flags::MODIFIERS);
-        assert!((has_modifier && modifier.is_some()) || (!has_modifier && modifier.is_none()));
+        if has_modifier != modifier.is_some() {
+            return Err(Errno::INVAL.into());
+        }
         let modifier = if let Some(modifier) = modifier {
             u64::from(modifier)
         } else {
@@ -390,9 +822,125 @@ pub trait Device: super::Device {
         Ok(from_u32(info.fb_id).unwrap())
     }
 
+    /// Add a framebuffer from dma-buf file descriptors, one per plane
+    ///
+    /// Each file descriptor is imported using [`Device::prime_fd_to_buffer`].
+    /// On success, the distinct GEM handles of the buffers are returned along
+    /// with the framebuffer. The framebuffer keeps its own references to the
+    /// buffers, so the handles may be closed using [`Device::close_buffer`] once
+    /// the caller no longer needs them.
+    ///
+    /// If importing or creating the framebuffer fails, the handles imported so
+    /// far are closed again. GEM handles are shared per open file though:
+    /// importing a buffer the caller already holds a handle for returns that
+    /// same handle. Handles listed in `held` are therefore never closed.
+    ///
+    /// `fds`, `pitches` and `offsets` have to be of the same length of at most
+    /// four, otherwise `EINVAL` is returned. [`FbCmd2Flags::MODIFIERS`] is added
+    /// to `flags` if `modifier` is given.
+    #[allow(clippy::too_many_arguments)]
+    fn add_framebuffer_from_dmabuf(
+        &self,
+        fds: &[BorrowedFd<'_>],
+        held: &[buffer::Handle],
+        format: DrmFourcc,
+        modifier: Option<DrmModifier>,
+        size: (u32, u32),
+        pitches: &[u32],
+        offsets: &[u32],
+        mut flags: FbCmd2Flags,
+    ) -> io::Result<(framebuffer::Handle, Vec<buffer::Handle>)> {
+        if fds.is_empty()
+            || fds.len() > 4
+            || pitches.len() != fds.len()
+            || offsets.len() != fds.len()
+        {
+            return Err(Errno::INVAL.into());
+        }
+
+        let mut handles = [0u32; 4];
+        let mut imported: Vec<buffer::Handle> = Vec::with_capacity(fds.len());
+        let release = |imported: &[buffer::Handle]| {
+            for buffer in imported.iter().filter(|buffer| !held.contains(buffer)) {
+                let _ = self.close_buffer(*buffer);
+            }
+        };
+        for (handle, fd) in handles.iter_mut().zip(fds) {
+            let buffer = match self.prime_fd_to_buffer(*fd) {
+                Ok(buffer) => buffer,
+                Err(err) => {
+                    release(&imported);
+                    return Err(err);
+                }
+            };
+            // Planes sharing a dma-buf are imported as the same handle
+            if !imported.contains(&buffer) {
+                imported.push(buffer);
+            }
+            *handle = buffer.into();
+        }
+
+        let modifier = modifier.map_or(0, |modifier| {
+            flags |= FbCmd2Flags::MODIFIERS;
+            u64::from(modifier)
+        });
+        let mut plane_pitches = [0u32; 4];
+        let mut plane_offsets = [0u32; 4];
+        let mut mods = [0u64; 4];
+        plane_pitches[..fds.len()].copy_from_slice(pitches);
+        plane_offsets[..fds.len()].copy_from_slice(offsets);
+        mods[..fds.len()].fill(modifier);
+
+        let info = ffi::mode::add_fb2(
+            self.as_fd(),
+            size.0,
+            size.1,
+            format as u32,
+            &handles,
+            &plane_pitches,
+            &plane_offsets,
+            &mods,
+            flags.bits(),
+        );
+
+        match info {
+            Ok(info) => Ok((from_u32(info.fb_id).unwrap(), imported)),
+            Err(err) => {
+                release(&imported);
+                Err(err)
+            }
+        }
+    }
+
     /// Mark parts of a framebuffer dirty
     fn dirty_framebuffer(&self, handle: framebuffer::Handle, clips: &[ClipRect]) -> io::Result<()> {
-        ffi::mode::dirty_fb(self.as_fd(), handle.into(), unsafe {
+        self.dirty_framebuffer_annotated(handle, DirtyAnnotation::None, clips)
+    }
+
+    /// Mark parts of a framebuffer dirty, describing how they changed
+    ///
+    /// Drivers for virtual and USB displays can use the annotation to transfer
+    /// updates more efficiently. With [`DirtyAnnotation::Copy`], `clips` holds
+    /// pairs of destination and source rectangles, and `EINVAL` is returned if
+    /// their number is odd.
+    fn dirty_framebuffer_annotated(
+        &self,
+        handle: framebuffer::Handle,
+        annotation: DirtyAnnotation,
+        clips: &[ClipRect],
+    ) -> io::Result<()> {
+        let (flags, color) = match annotation {
+            DirtyAnnotation::None => (0, 0),
+            DirtyAnnotation::Copy => {
+                if clips.len() % 2 != 0 {
+                    return Err(Errno::INVAL.into());
+                }
+                (ffi::DRM_MODE_FB_DIRTY_ANNOTATE_COPY, 0)
+            }
+            DirtyAnnotation::Fill(color) => (ffi::DRM_MODE_FB_DIRTY_ANNOTATE_FILL, color),
+        };
+
+        ffi::mode::dirty_fb_annotated(self.as_fd(), handle.into(), flags, color, unsafe {
             // SAFETY: ClipRect is repr(transparent) for drm_clip_rect
             core::slice::from_raw_parts(clips.as_ptr() as *const ffi::drm_clip_rect, clips.len())
         })?;
@@ -404,6 +952,19 @@ pub trait Device: super::Device {
         ffi::mode::rm_fb(self.as_fd(), handle.into())
     }
 
+    /// Destroy several framebuffers, returning the result for each of them
+    ///
+    /// Failing to destroy one framebuffer does not stop the others from being
+    /// destroyed, which is useful when tearing down. Framebuffers that are
+    /// still in use are usually removed from their crtcs and planes, disabling
+    /// them, though some drivers fail to destroy them instead.
+    fn destroy_framebuffers(&self, handles: &[framebuffer::Handle]) -> Vec<io::Result<()>> {
+        handles
+            .iter()
+            .map(|&handle| self.destroy_framebuffer(handle))
+            .collect()
+    }
+
     /// Returns information about a specific plane
     fn get_plane(&self, handle: plane::Handle) -> io::Result<plane::Info> {
         let mut formats = Vec::new();
@@ -421,9 +982,35 @@ pub trait Device: super::Device {
         Ok(plane)
     }
 
+    /// Returns the crtc and framebuffer a plane is currently bound to.
+    ///
+    /// This reads the atomic `CRTC_ID` and `FB_ID` properties of the plane,
+    /// which reflect the committed atomic state unlike the bindings reported by
+    /// [`Device::get_plane`]. Falls back to the latter if the properties are
+    /// not exposed, i.e. the [`Atomic`](crate::ClientCapability::Atomic)
+    /// client capability is not enabled.
+    fn plane_current_binding(
+        &self,
+        handle: plane::Handle,
+    ) -> io::Result<(Option<crtc::Handle>, Option<framebuffer::Handle>)> {
+        let crtc = find_property(self, handle, "CRTC_ID")?;
+        let fb = find_property(self, handle, "FB_ID")?;
+
+        match (crtc, fb) {
+            (Some((_, crtc)), Some((_, fb))) => Ok((from_u32(crtc as u32), from_u32(fb as u32))),
+            _ => {
+                let info = self.get_plane(handle)?;
+                Ok((info.crtc(), info.framebuffer()))
+            }
+        }
+    }
+
     /// Set plane state.
     ///
-    /// Providing no framebuffer clears the plane.
+    /// Providing no framebuffer clears the plane. `crtc_rect` is the
+    /// destination on the crtc as `(x, y, width, height)` in pixels, while
+    /// `src_rect` is the source within the framebuffer in 16.16 fixed point.
+    /// See [`Device::set_plane_rects`] for a variant with named fields.
     fn set_plane(
         &self,
         handle: plane::Handle,
@@ -452,6 +1039,39 @@ pub trait Device: super::Device {
         Ok(())
     }
 
+    /// Set plane state like [`Device::set_plane`], taking the destination and
+    /// source rectangles as [`CrtcRect`] and [`SrcRect`].
+    fn set_plane_rects(
+        &self,
+        handle: plane::Handle,
+        crtc: crtc::Handle,
+        framebuffer: Option<framebuffer::Handle>,
+        flags: u32,
+        crtc_rect: CrtcRect,
+        src_rect: SrcRect,
+    ) -> io::Result<()> {
+        self.set_plane(
+            handle,
+            crtc,
+            framebuffer,
+            flags,
+            (crtc_rect.x, crtc_rect.y, crtc_rect.w, crtc_rect.h),
+            (src_rect.x, src_rect.y, src_rect.w, src_rect.h),
+        )
+    }
+
+    /// Returns the cache used to look up property information by the helpers
+    /// of this trait accessing properties by name, such as
+    /// [`Device::set_plane_zpos`] or [`Device::set_connector_colorspace`].
+    ///
+    /// By default no cache is used and every lookup fetches the information of
+    /// all properties of the object again. Implementors calling these helpers
+    /// frequently should store a [`PropertyCache`] alongside the device and
+    /// return it here.
+    fn property_cache(&self) -> Option<&Mutex<PropertyCache>> {
+        None
+    }
+
     /// Returns information about a specific property.
     fn get_property(&self, handle: property::Handle) -> io::Result<property::Info> {
         let mut values = Vec::new();
@@ -535,12 +1155,15 @@ pub trait Device: super::Device {
 
     /// Create a property blob value from a given data blob
     fn create_property_blob<T>(&self, data: &T) -> io::Result<property::Value<'static>> {
-        let data = unsafe {
-            std::slice::from_raw_parts_mut(data as *const _ as *mut u8, mem::size_of::<T>())
-        };
-        let blob = ffi::mode::create_property_blob(self.as_fd(), data)?;
+        let id = create_blob(self, data)?;
+        Ok(property::Value::Blob(id))
+    }
 
-        Ok(property::Value::Blob(blob.blob_id.into()))
+    /// Create a property blob like [`Device::create_property_blob`], returning
+    /// a guard that destroys the blob once dropped.
+    fn create_property_blob_owned<T>(&self, data: &T) -> io::Result<PropertyBlob<'_, Self>> {
+        let id = create_blob(self, data)?;
+        Ok(PropertyBlob { device: self, id })
     }
 
     /// Get a property blob's data
@@ -551,27 +1174,30 @@ pub trait Device: super::Device {
     }
 
     /// Destroy a given property blob value
+    ///
+    /// A blob that does not exist (anymore) is not considered an error, so
+    /// destroying a blob twice is harmless. The kernel reports unknown blobs
+    /// with `EINVAL`, `ENOENT` is tolerated as well.
     fn destroy_property_blob(&self, blob: u64) -> io::Result<()> {
-        ffi::mode::destroy_property_blob(self.as_fd(), blob as u32)?;
-
+        unknown_object_as_none(ffi::mode::destroy_property_blob(self.as_fd(), blob as u32))?;
         Ok(())
     }
 
     /// Returns the set of [`Mode`]s that a particular connector supports.
+    ///
+    /// The connector is not probed, so the modes are the ones found by the last
+    /// probe. Use [`Device::get_connector`] to get modes and connection state
+    /// with a single request.
     fn get_modes(&self, handle: connector::Handle) -> io::Result<Vec<Mode>> {
-        let mut modes = Vec::new();
-
-        let _ffi_info = ffi::mode::get_connector(
-            self.as_fd(),
-            handle.into(),
-            None,
-            None,
-            Some(&mut modes),
-            None,
-            false,
-        )?;
+        get_connector_modes(self, handle, false)
+    }
 
-        Ok(Mode::wrap_vec(modes))
+    /// Returns the set of [`Mode`]s that a particular connector supports, after
+    /// force-probing it.
+    ///
+    /// See [`Device::get_connector`] on the implications of force-probing.
+    fn get_modes_force(&self, handle: connector::Handle) -> io::Result<Vec<Mode>> {
+        get_connector_modes(self, handle, true)
     }
 
     /// Gets a list of property handles and values for this resource.
@@ -592,10 +1218,340 @@ pub trait Device: super::Device {
             prop_vals,
         };
 
-        Ok(prop_val_set)
+        Ok(prop_val_set)
+    }
+
+    /// Returns the mutable properties of a resource together with their current values.
+    ///
+    /// These are the properties that can be set, e.g. in an atomic commit.
+    /// Immutable properties, such as `EDID` or `IN_FORMATS`, are left out.
+    fn get_mutable_properties<T: ResourceHandle>(
+        &self,
+        handle: T,
+    ) -> io::Result<Vec<(property::Info, property::RawValue)>> {
+        let mut props = Vec::new();
+        for (prop, value) in self.get_properties(handle)? {
+            let info = self.get_property(prop)?;
+            if info.mutable() {
+                props.push((info, value));
+            }
+        }
+        Ok(props)
+    }
+
+    /// Returns whether a connector drives a display not meant for the desktop,
+    /// such as a VR headset, as reported by its `non-desktop` property.
+    ///
+    /// Connectors without the property are desktop displays.
+    fn connector_non_desktop(&self, handle: connector::Handle) -> io::Result<bool> {
+        Ok(find_property(self, handle, "non-desktop")?.map_or(false, |(_, value)| value != 0))
+    }
+
+    /// Returns the placement of a connector within a tiled display.
+    ///
+    /// Returns [`None`] if the connector does not drive a tile of a tiled display.
+    fn get_connector_tile(&self, handle: connector::Handle) -> io::Result<Option<tile::TileInfo>> {
+        let blob = match find_property(self, handle, "TILE")? {
+            Some((_, 0)) | None => return Ok(None),
+            Some((_, blob)) => blob,
+        };
+        let data = self.get_property_blob(blob)?;
+        Ok(tile::TileInfo::parse(&data))
+    }
+
+    /// Returns how a connector is reached, e.g. through a DisplayPort MST topology.
+    ///
+    /// Returns [`None`] if the connector does not have a path.
+    fn get_connector_path(&self, handle: connector::Handle) -> io::Result<Option<tile::PathInfo>> {
+        let blob = match find_property(self, handle, "PATH")? {
+            Some((_, 0)) | None => return Ok(None),
+            Some((_, blob)) => blob,
+        };
+        let data = self.get_property_blob(blob)?;
+        Ok(tile::PathInfo::parse(&data))
+    }
+
+    /// Sets a property of a connector by name, using the legacy property API.
+    ///
+    /// Fails with `ENOENT` if the connector has no property called `name`.
+    fn set_connector_property_by_name(
+        &self,
+        handle: connector::Handle,
+        name: &str,
+        value: property::Value<'_>,
+    ) -> io::Result<()> {
+        let (info, _) = find_property(self, handle, name)?.ok_or(Errno::NOENT)?;
+        self.set_property(handle, info.handle(), value.into())
+    }
+
+    /// Returns the type of a plane, as reported by its `type` property.
+    ///
+    /// The property is only exposed to clients that enabled the
+    /// [`UniversalPlanes`](crate::ClientCapability::UniversalPlanes) client
+    /// capability, `ENOENT` is returned otherwise.
+    fn plane_type(&self, handle: plane::Handle) -> io::Result<PlaneType> {
+        let (_, value) = find_property(self, handle, "type")?.ok_or(Errno::NOENT)?;
+        match value as u32 {
+            ffi::DRM_PLANE_TYPE_OVERLAY => Ok(PlaneType::Overlay),
+            ffi::DRM_PLANE_TYPE_PRIMARY => Ok(PlaneType::Primary),
+            ffi::DRM_PLANE_TYPE_CURSOR => Ok(PlaneType::Cursor),
+            _ => Err(Errno::INVAL.into()),
+        }
+    }
+
+    /// Returns information about all planes that can be used with the given crtc.
+    ///
+    /// Primary planes are listed first, followed by overlay and cursor planes.
+    /// Planes without a `type` property, as reported without the
+    /// [`UniversalPlanes`](crate::ClientCapability::UniversalPlanes) client
+    /// capability, are treated as overlay planes. Fails with `ENOENT` if the crtc
+    /// does not exist.
+    fn planes_for_crtc(&self, crtc: crtc::Handle) -> io::Result<Vec<plane::Info>> {
+        let index = self
+            .resource_handles()?
+            .crtc_index(crtc)
+            .ok_or(Errno::NOENT)?;
+
+        let mut planes = Vec::new();
+        for handle in self.plane_handles()? {
+            let info = self.get_plane(handle)?;
+            if info.possible_crtcs().0 & (1 << index) == 0 {
+                continue;
+            }
+            let order = match self.plane_type(handle) {
+                Ok(PlaneType::Primary) => 0,
+                Ok(PlaneType::Cursor) => 2,
+                _ => 1,
+            };
+            planes.push((order, info));
+        }
+        planes.sort_by_key(|&(order, _)| order);
+
+        Ok(planes.into_iter().map(|(_, info)| info).collect())
+    }
+
+    /// Returns the primary plane of the given crtc.
+    ///
+    /// A compatible primary plane currently bound to the crtc is preferred over
+    /// other compatible primary planes. Requires the
+    /// [`UniversalPlanes`](crate::ClientCapability::UniversalPlanes) client
+    /// capability, otherwise no primary planes are exposed and [`None`] is returned.
+    fn primary_plane_for_crtc(&self, crtc: crtc::Handle) -> io::Result<Option<plane::Handle>> {
+        find_plane_for_crtc(self, crtc, PlaneType::Primary)
+    }
+
+    /// Returns the cursor plane of the given crtc.
+    ///
+    /// Works like [`Device::primary_plane_for_crtc`], looking for cursor planes.
+    fn cursor_plane_for_crtc(&self, crtc: crtc::Handle) -> io::Result<Option<plane::Handle>> {
+        find_plane_for_crtc(self, crtc, PlaneType::Cursor)
+    }
+
+    /// Returns the range of values supported by the `zpos` property of a plane.
+    ///
+    /// Planes with a higher `zpos` are stacked above those with a lower one. If
+    /// the property is immutable, the stacking order of the plane is fixed and
+    /// the range covers a single value. Fails with `ENOENT` if the plane has no
+    /// such property, and with `EINVAL` if it is not a range property.
+    fn plane_zpos_range(&self, handle: plane::Handle) -> io::Result<(i64, i64)> {
+        let (info, _) = find_property(self, handle, "zpos")?.ok_or(Errno::NOENT)?;
+        // Ranges of (0, 1) are reported as booleans by `get_property`, so the
+        // range is read from the raw property instead.
+        let mut values = Vec::new();
+        let raw =
+            ffi::mode::get_property(self.as_fd(), info.handle().into(), Some(&mut values), None)?;
+        decode_range(ModePropFlags::from_bits_truncate(raw.flags), &values)
+    }
+
+    /// Sets the `zpos` property of a plane, controlling its stacking order.
+    ///
+    /// Atomic clients should stage the change on their request using
+    /// [`atomic::AtomicModeReq::set_plane_zpos`] instead.
+    ///
+    /// Fails with `ENOENT` if the plane has no such property.
+    fn set_plane_zpos(&self, handle: plane::Handle, zpos: i64) -> io::Result<()> {
+        let (info, _) = find_property(self, handle, "zpos")?.ok_or(Errno::NOENT)?;
+        self.set_property(
+            handle,
+            info.handle(),
+            property::Value::SignedRange(zpos).into(),
+        )
+    }
+
+    /// Sets the `alpha` property of a plane, controlling the opacity of the
+    /// whole plane from transparent (`0`) to opaque (`0xffff`).
+    ///
+    /// Fails with `ENOENT` if the plane has no such property.
+    fn set_plane_alpha(&self, handle: plane::Handle, alpha: u16) -> io::Result<()> {
+        let (info, _) = find_property(self, handle, "alpha")?.ok_or(Errno::NOENT)?;
+        self.set_property(handle, info.handle(), alpha.into())
+    }
+
+    /// Sets the `pixel blend mode` property of a plane.
+    ///
+    /// Fails with `ENOENT` if the plane has no such property, and with `EINVAL`
+    /// if it does not support the given mode.
+    fn set_plane_blend_mode(
+        &self,
+        handle: plane::Handle,
+        mode: plane::BlendMode,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "pixel blend mode", mode.as_str())
+    }
+
+    /// Sets the `SCALING_FILTER` property of a plane or crtc, selecting the
+    /// filter used when its contents are scaled.
+    ///
+    /// Fails with `ENOENT` if the object has no such property, and with
+    /// `EINVAL` if it does not support the given filter.
+    fn set_scaling_filter<T: ResourceHandle>(
+        &self,
+        handle: T,
+        filter: ScalingFilter,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "SCALING_FILTER", filter.as_str())
+    }
+
+    /// Returns the rotations and reflections supported by the `rotation`
+    /// property of a plane.
+    ///
+    /// Fails with `ENOENT` if the plane has no such property.
+    fn plane_supported_rotations(&self, handle: plane::Handle) -> io::Result<Rotation> {
+        let (info, _) = find_property(self, handle, "rotation")?.ok_or(Errno::NOENT)?;
+        let mut values = Vec::new();
+        let _ =
+            ffi::mode::get_property(self.as_fd(), info.handle().into(), Some(&mut values), None)?;
+
+        // The values of bitmask properties are bit positions
+        let bits = values
+            .into_iter()
+            .filter(|&bit| bit < 32)
+            .fold(0, |bits, bit| bits | (1 << bit));
+        Ok(Rotation::from_bits_truncate(bits))
+    }
+
+    /// Sets the `rotation` property of a plane.
+    ///
+    /// Fails with `ENOENT` if the plane has no such property.
+    fn set_plane_rotation(&self, handle: plane::Handle, rotation: Rotation) -> io::Result<()> {
+        let (info, _) = find_property(self, handle, "rotation")?.ok_or(Errno::NOENT)?;
+        self.set_property(handle, info.handle(), rotation.bits().into())
+    }
+
+    /// Sets the `DPMS` property of a connector, controlling the power state of the sink.
+    ///
+    /// # Notes
+    ///
+    /// Drivers supporting atomic modesetting only emulate this property: every
+    /// state other than [`connector::DpmsState::On`] turns the crtc driving
+    /// the connector off. The kernel rejects `DPMS` in atomic commits, so
+    /// atomic clients should stage the change on the `ACTIVE` property of the
+    /// crtc using [`atomic::AtomicModeReq::set_connector_dpms`] instead.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property.
+    fn set_connector_dpms(
+        &self,
+        handle: connector::Handle,
+        state: connector::DpmsState,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "DPMS", state.as_str())
+    }
+
+    /// Sets the `Broadcast RGB` property of a connector, selecting the RGB
+    /// quantization range of the output.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property, and with
+    /// `EINVAL` if it does not support the given range.
+    fn set_broadcast_rgb(
+        &self,
+        handle: connector::Handle,
+        range: connector::BroadcastRgb,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "Broadcast RGB", range.as_str())
+    }
+
+    /// Sets the `Content Protection` property of a connector, requesting or
+    /// dropping HDCP protection of the output.
+    ///
+    /// Only [`connector::ContentProtection::Undesired`] and
+    /// [`connector::ContentProtection::Desired`] may be set. Once protection is
+    /// established, the kernel changes the state to
+    /// [`connector::ContentProtection::Enabled`], which can be observed using
+    /// [`Device::content_protection_state`].
+    ///
+    /// Fails with `ENOENT` if the connector has no such property.
+    fn set_content_protection(
+        &self,
+        handle: connector::Handle,
+        state: connector::ContentProtection,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "Content Protection", state.as_str())
+    }
+
+    /// Returns the current state of the `Content Protection` property of a connector.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property.
+    fn content_protection_state(
+        &self,
+        handle: connector::Handle,
+    ) -> io::Result<connector::ContentProtection> {
+        let name = get_enum_property(self, handle, "Content Protection")?;
+        connector::ContentProtection::from_name(&name).ok_or_else(|| Errno::INVAL.into())
+    }
+
+    /// Sets the `content type` property of a connector, signalling the type of
+    /// the displayed content to HDMI sinks.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property.
+    fn set_content_type(
+        &self,
+        handle: connector::Handle,
+        content_type: connector::ContentType,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "content type", content_type.as_str())
+    }
+
+    /// Returns the current value of the `content type` property of a connector.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property.
+    fn content_type(&self, handle: connector::Handle) -> io::Result<connector::ContentType> {
+        let name = get_enum_property(self, handle, "content type")?;
+        connector::ContentType::from_name(&name).ok_or_else(|| Errno::INVAL.into())
+    }
+
+    /// Sets the `scaling mode` property of a connector, controlling how modes
+    /// other than the native mode of a panel are displayed.
+    ///
+    /// Fails with `ENOENT` if the connector has no such property, and with
+    /// `EINVAL` if it does not support the given mode.
+    fn set_scaling_mode(
+        &self,
+        handle: connector::Handle,
+        mode: connector::ScalingMode,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "scaling mode", mode.as_str())
+    }
+
+    /// Sets the `Colorspace` property of a connector, signalling the
+    /// colorimetry of the output to the sink.
+    ///
+    /// HDR output usually requires this to be set together with the
+    /// `HDR_OUTPUT_METADATA` property. Fails with `ENOENT` if the connector has
+    /// no such property, and with `EINVAL` if it does not support the given
+    /// colorspace.
+    fn set_connector_colorspace(
+        &self,
+        handle: connector::Handle,
+        colorspace: connector::Colorspace,
+    ) -> io::Result<()> {
+        set_enum_property(self, handle, "Colorspace", colorspace.as_str())
     }
 
     /// Receive the currently set gamma ramp of a crtc
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the crtc has no legacy gamma
+    /// ramp, which is the case for drivers only exposing the atomic
+    /// `GAMMA_LUT` property.
     fn get_gamma(
         &self,
         crtc: crtc::Handle,
@@ -603,27 +1559,20 @@ pub trait Device: super::Device {
         green: &mut [u16],
         blue: &mut [u16],
     ) -> io::Result<()> {
-        let crtc_info = self.get_crtc(crtc)?;
-        if crtc_info.gamma_length as usize > red.len()
-            || crtc_info.gamma_length as usize > green.len()
-            || crtc_info.gamma_length as usize > blue.len()
-        {
+        let length = legacy_gamma_length(self, crtc)?;
+        if length > red.len() || length > green.len() || length > blue.len() {
             return Err(Errno::INVAL.into());
         }
 
-        ffi::mode::get_gamma(
-            self.as_fd(),
-            crtc.into(),
-            crtc_info.gamma_length as usize,
-            red,
-            green,
-            blue,
-        )?;
+        ffi::mode::get_gamma(self.as_fd(), crtc.into(), length, red, green, blue)?;
 
         Ok(())
     }
 
     /// Set a gamma ramp for the given crtc
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the crtc has no legacy gamma
+    /// ramp, see [`Device::get_gamma`].
     fn set_gamma(
         &self,
         crtc: crtc::Handle,
@@ -631,26 +1580,87 @@ pub trait Device: super::Device {
         green: &[u16],
         blue: &[u16],
     ) -> io::Result<()> {
-        let crtc_info = self.get_crtc(crtc)?;
-        if crtc_info.gamma_length as usize > red.len()
-            || crtc_info.gamma_length as usize > green.len()
-            || crtc_info.gamma_length as usize > blue.len()
-        {
+        let length = legacy_gamma_length(self, crtc)?;
+        if length > red.len() || length > green.len() || length > blue.len() {
+            return Err(Errno::INVAL.into());
+        }
+
+        ffi::mode::set_gamma(self.as_fd(), crtc.into(), length, red, green, blue)?;
+
+        Ok(())
+    }
+
+    /// Receive the currently set gamma ramp of a crtc, sized to its gamma length
+    fn get_gamma_vecs(&self, crtc: crtc::Handle) -> io::Result<(Vec<u16>, Vec<u16>, Vec<u16>)> {
+        let length = legacy_gamma_length(self, crtc)?;
+        let mut red = vec![0; length];
+        let mut green = vec![0; length];
+        let mut blue = vec![0; length];
+
+        ffi::mode::get_gamma(
+            self.as_fd(),
+            crtc.into(),
+            length,
+            &mut red,
+            &mut green,
+            &mut blue,
+        )?;
+
+        Ok((red, green, blue))
+    }
+
+    /// Set a gamma ramp for the given crtc
+    ///
+    /// Returns `EINVAL` unless all lookup tables of `ramp` match the gamma
+    /// length of the crtc.
+    fn set_gamma_ramp(&self, crtc: crtc::Handle, ramp: &crtc::GammaRamp) -> io::Result<()> {
+        let length = legacy_gamma_length(self, crtc)?;
+        if ramp.length() != Some(length) {
             return Err(Errno::INVAL.into());
         }
 
         ffi::mode::set_gamma(
             self.as_fd(),
             crtc.into(),
-            crtc_info.gamma_length as usize,
-            red,
-            green,
-            blue,
+            length,
+            &ramp.red,
+            &ramp.green,
+            &ramp.blue,
         )?;
 
         Ok(())
     }
 
+    /// Set a linear gamma ramp for the given crtc, passing colors through unchanged
+    fn set_gamma_identity(&self, crtc: crtc::Handle) -> io::Result<()> {
+        let length = legacy_gamma_length(self, crtc)?;
+        self.set_gamma_ramp(crtc, &crtc::GammaRamp::linear(length))
+    }
+
+    /// Resets the color management of a crtc to pass colors through unchanged
+    ///
+    /// If the crtc exposes the `DEGAMMA_LUT`, `CTM` or `GAMMA_LUT` properties,
+    /// they are cleared in a single atomic commit, which bypasses the respective
+    /// stages and requires the [`Atomic`](crate::ClientCapability::Atomic) client
+    /// capability. Otherwise a linear gamma ramp is set using
+    /// [`Device::set_gamma_identity`], if the crtc supports gamma ramps at all.
+    fn reset_crtc_color(&self, crtc: crtc::Handle) -> io::Result<()> {
+        let mut req = atomic::AtomicModeReq::new();
+        for name in ["DEGAMMA_LUT", "CTM", "GAMMA_LUT"] {
+            if let Some((info, _)) = find_property(self, crtc, name)? {
+                req.add_raw_property(crtc.into(), info.handle(), 0);
+            }
+        }
+        if !req.objects.is_empty() {
+            return self.atomic_commit(AtomicCommitFlags::empty(), req);
+        }
+
+        if self.get_crtc(crtc)?.gamma_length() > 0 {
+            self.set_gamma_identity(crtc)?;
+        }
+        Ok(())
+    }
+
     /// Open a GEM buffer handle by name
     fn open_buffer(&self, name: buffer::Name) -> io::Result<buffer::Handle> {
         let info = drm_ffi::gem::open(self.as_fd(), name.into())?;
@@ -658,30 +1668,74 @@ pub trait Device: super::Device {
     }
 
     /// Close a GEM buffer handle
+    ///
+    /// GEM handles are reference counted per open file: opening the same buffer
+    /// multiple times, e.g. via [`Device::open_buffer`] or
+    /// [`Device::prime_fd_to_buffer`], returns the same handle, while a single
+    /// close releases it. Callers sharing handles must therefore track their
+    /// own references and close a handle only once it is no longer in use.
     fn close_buffer(&self, handle: buffer::Handle) -> io::Result<()> {
         let _info = drm_ffi::gem::close(self.as_fd(), handle.into())?;
         Ok(())
     }
 
+    /// Close a GEM buffer handle, tolerating handles that were already closed
+    ///
+    /// Returns `Ok(true)` if the handle was closed by this call and `Ok(false)`
+    /// if it did not refer to a buffer anymore. Both `ENOENT` and `EINVAL` are
+    /// treated as an unknown handle, the latter being what the kernel reports.
+    fn try_close_buffer(&self, handle: buffer::Handle) -> io::Result<bool> {
+        let closed = unknown_object_as_none(drm_ffi::gem::close(self.as_fd(), handle.into()))?;
+        Ok(closed.is_some())
+    }
+
     /// Create a new dumb buffer with a given size and pixel format
+    ///
+    /// For single-plane formats known to [`buffer::FormatInfo`], `bpp` has to
+    /// match the bits per pixel of the format, otherwise `EINVAL` is returned.
     fn create_dumb_buffer(
         &self,
         size: (u32, u32),
         format: buffer::DrmFourcc,
         bpp: u32,
     ) -> io::Result<DumbBuffer> {
+        if let Some(info) = buffer::FormatInfo::from_format(format) {
+            if info.num_planes() == 1 && info.bpp(0) != bpp {
+                return Err(Errno::INVAL.into());
+            }
+        }
+
         let info = drm_ffi::mode::dumbbuffer::create(self.as_fd(), size.0, size.1, bpp, 0)?;
 
         let dumb = DumbBuffer {
             size: (info.width, info.height),
             length: info.size as usize,
             format,
+            bpp: info.bpp,
             pitch: info.pitch,
             handle: from_u32(info.handle).unwrap(),
         };
 
         Ok(dumb)
     }
+
+    /// Create a new dumb buffer with a given size and pixel format, deriving
+    /// the bits per pixel from the format
+    ///
+    /// Only single-plane formats known to [`buffer::FormatInfo`] are supported,
+    /// `EINVAL` is returned for all others.
+    fn create_dumb_buffer_for_format(
+        &self,
+        size: (u32, u32),
+        format: buffer::DrmFourcc,
+    ) -> io::Result<DumbBuffer> {
+        let info = buffer::FormatInfo::from_format(format).ok_or(Errno::INVAL)?;
+        if info.num_planes() != 1 {
+            return Err(Errno::INVAL.into());
+        }
+        self.create_dumb_buffer(size, format, info.bpp(0))
+    }
+
     /// Map the buffer for access
     fn map_dumb_buffer<'a>(&self, buffer: &'a mut DumbBuffer) -> io::Result<DumbMapping<'a>> {
         let info = drm_ffi::mode::dumbbuffer::map(self.as_fd(), buffer.handle.into(), 0, 0)?;
@@ -698,6 +1752,7 @@ pub trait Device: super::Device {
         let mapping = DumbMapping {
             _phantom: std::marker::PhantomData,
             map: unsafe { std::slice::from_raw_parts_mut(map as *mut _, buffer.length) },
+            row_len: buffer.row_len(),
         };
 
         Ok(mapping)
@@ -765,22 +1820,160 @@ pub trait Device: super::Device {
         Ok(())
     }
 
+    /// Moves a cursor plane on the crtc it is bound to, replacing [`Device::move_cursor`]
+    ///
+    /// Only the `CRTC_X` and `CRTC_Y` properties of the plane are changed, using
+    /// a non-blocking atomic commit. Returns `EINVAL` if the plane is not bound to
+    /// `crtc`.
+    fn move_cursor_plane(
+        &self,
+        plane: plane::Handle,
+        crtc: crtc::Handle,
+        pos: (i32, i32),
+    ) -> io::Result<()> {
+        if self.get_plane(plane)?.crtc() != Some(crtc) {
+            return Err(Errno::INVAL.into());
+        }
+
+        let mut req = atomic::AtomicModeReq::new();
+        add_property_by_name(self, &mut req, plane, "CRTC_X", pos.0 as i64 as u64)?;
+        add_property_by_name(self, &mut req, plane, "CRTC_Y", pos.1 as i64 as u64)?;
+        self.atomic_commit(AtomicCommitFlags::NONBLOCK, req)
+    }
+
     /// Request an atomic commit with given flags and property-value pair for a list of objects.
+    ///
+    /// Property blobs owned by the request are destroyed afterwards, whether the commit
+    /// succeeded or not. This includes commits using [`AtomicCommitFlags::TEST_ONLY`],
+    /// use [`Device::atomic_test`] to keep the request instead.
     fn atomic_commit(
+        &self,
+        flags: AtomicCommitFlags,
+        req: atomic::AtomicModeReq,
+    ) -> io::Result<()> {
+        self.atomic_commit_with_user_data(flags, req, 0)
+    }
+
+    /// Request an atomic commit like [`Device::atomic_commit`], attaching `user_data` to the
+    /// page flip events requested by [`AtomicCommitFlags::PAGE_FLIP_EVENT`].
+    ///
+    /// The value is returned in [`PageFlipEvent::user_data`], allowing events to be
+    /// correlated with the commit that caused them.
+    fn atomic_commit_with_user_data(
         &self,
         flags: AtomicCommitFlags,
         mut req: atomic::AtomicModeReq,
+        user_data: u64,
     ) -> io::Result<()> {
-        drm_ffi::mode::atomic_commit(
+        let result = drm_ffi::mode::atomic_commit_with_user_data(
             self.as_fd(),
             flags.bits(),
             unsafe { &mut *(&mut *req.objects as *mut _ as *mut [u32]) },
             &mut req.count_props_per_object,
             unsafe { &mut *(&mut *req.props as *mut _ as *mut [u32]) },
             &mut req.values,
+            user_data,
+        );
+        // The kernel holds its own references to blobs it uses
+        let _ = req.cleanup(self);
+        result
+    }
+
+    /// Test whether `req` would be accepted by [`Device::atomic_commit`], without
+    /// applying it.
+    ///
+    /// The request is committed using [`AtomicCommitFlags::TEST_ONLY`] in addition to
+    /// `flags`. Unlike [`Device::atomic_commit`], the request is borrowed, so it
+    /// keeps its property blobs and can be committed afterwards.
+    fn atomic_test(&self, flags: AtomicCommitFlags, req: &atomic::AtomicModeReq) -> io::Result<()> {
+        // Clones do not own the blobs of the request
+        self.atomic_commit(flags | AtomicCommitFlags::TEST_ONLY, req.clone())
+    }
+
+    /// Queue the changes of `req` for the next vblank without waiting for them
+    ///
+    /// The request is committed using [`AtomicCommitFlags::NONBLOCK`] and
+    /// [`AtomicCommitFlags::PAGE_FLIP_EVENT`]. Once applied, a [`PageFlipEvent`]
+    /// is delivered for each crtc affected by the request, whose
+    /// [`PageFlipEvent::user_data`] is set to `user_data`. Passing a distinct value
+    /// for each flip, such as a frame counter or an index into the caller's own
+    /// bookkeeping, allows matching events to the flips they complete.
+    ///
+    /// Until the event arrives, further non-blocking commits affecting the same
+    /// crtcs fail with `EBUSY`.
+    fn atomic_flip(&self, req: atomic::AtomicModeReq, user_data: u64) -> io::Result<()> {
+        self.atomic_commit_with_user_data(
+            AtomicCommitFlags::NONBLOCK | AtomicCommitFlags::PAGE_FLIP_EVENT,
+            req,
+            user_data,
         )
     }
 
+    /// Request an atomic commit like [`Device::atomic_commit`], classifying
+    /// common failures using [`AtomicCommitError`].
+    fn atomic_commit_classified(
+        &self,
+        flags: AtomicCommitFlags,
+        req: atomic::AtomicModeReq,
+    ) -> Result<(), AtomicCommitError> {
+        self.atomic_commit(flags, req)
+            .map_err(AtomicCommitError::from)
+    }
+
+    /// Request an atomic commit like [`Device::atomic_commit`] and block until
+    /// it has been applied.
+    ///
+    /// The commit always requests a page flip event, which is matched using
+    /// [`PageFlipEvent::user_data`]. All other events read from the device in
+    /// the meantime, such as events of other crtcs, are appended to `skipped`
+    /// in the order they were read, so the caller can process them afterwards.
+    /// If a commit affects multiple crtcs, the event of the first crtc to
+    /// complete is returned, and events of the other crtcs read along with it
+    /// end up in `skipped` as well.
+    ///
+    /// If `timeout` elapses before the event arrives, `ETIMEDOUT` is returned.
+    /// Events read until then are still appended to `skipped`.
+    fn atomic_commit_blocking(
+        &self,
+        flags: AtomicCommitFlags,
+        req: atomic::AtomicModeReq,
+        timeout: Option<Duration>,
+        skipped: &mut Vec<Event>,
+    ) -> io::Result<PageFlipEvent>
+    where
+        Self: Sized,
+    {
+        static NEXT_USER_DATA: AtomicU64 = AtomicU64::new(1);
+        let user_data = NEXT_USER_DATA.fetch_add(1, Ordering::Relaxed);
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        self.atomic_commit_with_user_data(
+            flags | AtomicCommitFlags::PAGE_FLIP_EVENT,
+            req,
+            user_data,
+        )?;
+
+        let mut found = None;
+        loop {
+            let remaining =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            for event in self.wait_for_event(remaining)? {
+                match event {
+                    Event::PageFlip(event) if found.is_none() && event.user_data == user_data => {
+                        found = Some(event);
+                    }
+                    event => skipped.push(event),
+                }
+            }
+            if let Some(event) = found {
+                return Ok(event);
+            }
+            if remaining == Some(Duration::ZERO) {
+                return Err(Errno::TIMEDOUT.into());
+            }
+        }
+    }
+
     /// Convert a prime file descriptor to a GEM buffer handle
     fn prime_fd_to_buffer(&self, fd: BorrowedFd<'_>) -> io::Result<buffer::Handle> {
         let info = ffi::gem::fd_to_handle(self.as_fd(), fd)?;
@@ -788,12 +1981,37 @@ pub trait Device: super::Device {
     }
 
     /// Convert a GEM buffer handle to a prime file descriptor
+    ///
+    /// `flags` is a combination of [`crate::CLOEXEC`] and [`crate::RDWR`].
+    /// Without [`crate::RDWR`], the resulting dma-buf can only be mapped for
+    /// reading. [`Device::export_buffer_readonly`] and
+    /// [`Device::export_buffer_rdwr`] cover the common cases.
     fn buffer_to_prime_fd(&self, handle: buffer::Handle, flags: u32) -> io::Result<OwnedFd> {
         let info = ffi::gem::handle_to_fd(self.as_fd(), handle.into(), flags)?;
         Ok(unsafe { OwnedFd::from_raw_fd(info.fd) })
     }
 
+    /// Export a GEM buffer handle as a dma-buf that can only be mapped for reading
+    ///
+    /// The file descriptor is created with `O_CLOEXEC`.
+    fn export_buffer_readonly(&self, handle: buffer::Handle) -> io::Result<OwnedFd> {
+        self.buffer_to_prime_fd(handle, crate::CLOEXEC)
+    }
+
+    /// Export a GEM buffer handle as a dma-buf that can be mapped for reading
+    /// and writing, e.g. for CPU access by the importer
+    ///
+    /// The file descriptor is created with `O_CLOEXEC`. Some drivers reject
+    /// writable exports of buffers they cannot keep coherent.
+    fn export_buffer_rdwr(&self, handle: buffer::Handle) -> io::Result<OwnedFd> {
+        self.buffer_to_prime_fd(handle, crate::CLOEXEC | crate::RDWR)
+    }
+
     /// Queue a page flip on the given crtc
+    ///
+    /// Targeting a specific vblank using `target_sequence` requires the
+    /// [`PageFlipTarget`](super::DriverCapability::PageFlipTarget) capability.
+    /// If the driver lacks it, `EOPNOTSUPP` is returned without attempting the flip.
     fn page_flip(
         &self,
         handle: crtc::Handle,
@@ -801,6 +2019,12 @@ pub trait Device: super::Device {
         flags: PageFlipFlags,
         target_sequence: Option<PageFlipTarget>,
     ) -> io::Result<()> {
+        if target_sequence.is_some()
+            && !self.has_capability(crate::DriverCapability::PageFlipTarget)?
+        {
+            return Err(Errno::OPNOTSUPP.into());
+        }
+
         let mut flags = flags.bits();
 
         let sequence = match target_sequence {
@@ -826,6 +2050,45 @@ pub trait Device: super::Device {
         Ok(())
     }
 
+    /// Waits for a vblank on the given crtc.
+    ///
+    /// Unlike [`super::Device::wait_vblank`], which expects the index of the
+    /// crtc, this looks up the index of `crtc` in [`Device::resource_handles`].
+    /// Crtcs other than the first one require the
+    /// [`VBlankHighCRTC`](super::DriverCapability::VBlankHighCRTC) capability,
+    /// otherwise `EOPNOTSUPP` is returned.
+    fn wait_vblank_for_crtc(
+        &self,
+        crtc: crtc::Handle,
+        target_sequence: crate::VblankWaitTarget,
+        flags: crate::VblankWaitFlags,
+        user_data: usize,
+    ) -> io::Result<crate::VblankWaitReply> {
+        let index = self
+            .resource_handles()?
+            .crtc_index(crtc)
+            .ok_or(Errno::NOENT)?;
+
+        if index > 0 && !self.has_capability(crate::DriverCapability::VBlankHighCRTC)? {
+            return Err(Errno::OPNOTSUPP.into());
+        }
+
+        self.wait_vblank(target_sequence, flags, index, user_data)
+    }
+
+    /// Returns the current vblank count of a crtc and the time the vblank with
+    /// this count started, in `CLOCK_MONOTONIC`.
+    ///
+    /// The count is the one reported as frame of [`Event::Vblank`] and
+    /// [`Event::PageFlip`], but 64 bits wide. If the crtc is
+    /// not active, the last values recorded before it was disabled are
+    /// returned.
+    fn crtc_vblank_count(&self, crtc: crtc::Handle) -> io::Result<(u64, Duration)> {
+        let info = ffi::mode::crtc_get_sequence(self.as_fd(), crtc.into())?;
+        let time = Duration::from_nanos(info.sequence_ns.max(0) as u64);
+        Ok((info.sequence, time))
+    }
+
     /// Creates a syncobj.
     fn create_syncobj(&self, signalled: bool) -> io::Result<syncobj::Handle> {
         let info = ffi::syncobj::create(self.as_fd(), signalled)?;
@@ -848,6 +2111,24 @@ pub trait Device: super::Device {
         Ok(unsafe { OwnedFd::from_raw_fd(info.fd) })
     }
 
+    /// Creates a syncobj and exports it like [`Self::syncobj_to_fd`].
+    ///
+    /// The syncobj is destroyed again if the export fails.
+    fn create_syncobj_fd(
+        &self,
+        signalled: bool,
+        export_sync_file: bool,
+    ) -> io::Result<(syncobj::Handle, OwnedFd)> {
+        let handle = self.create_syncobj(signalled)?;
+        match self.syncobj_to_fd(handle, export_sync_file) {
+            Ok(fd) => Ok((handle, fd)),
+            Err(err) => {
+                let _ = self.destroy_syncobj(handle);
+                Err(err)
+            }
+        }
+    }
+
     /// Imports a file descriptor exported by [`Self::syncobj_to_fd`] back into a process-local handle.
     fn fd_to_syncobj(
         &self,
@@ -859,6 +2140,8 @@ pub trait Device: super::Device {
     }
 
     /// Waits for one or more syncobjs to become signalled.
+    ///
+    /// Returns `Ok(0)` right away if `handles` is empty, as there is nothing to wait on.
     fn syncobj_wait(
         &self,
         handles: &[syncobj::Handle],
@@ -866,6 +2149,9 @@ pub trait Device: super::Device {
         wait_all: bool,
         wait_for_submit: bool,
     ) -> io::Result<u32> {
+        if handles.is_empty() {
+            return Ok(0);
+        }
         let info = ffi::syncobj::wait(
             self.as_fd(),
             bytemuck::cast_slice(handles),
@@ -889,6 +2175,8 @@ pub trait Device: super::Device {
     }
 
     /// Waits for one or more specific timeline syncobj points.
+    ///
+    /// Returns `Ok(0)` right away if `handles` is empty, as there is nothing to wait on.
     fn syncobj_timeline_wait(
         &self,
         handles: &[syncobj::Handle],
@@ -898,6 +2186,9 @@ pub trait Device: super::Device {
         wait_for_submit: bool,
         wait_available: bool,
     ) -> io::Result<u32> {
+        if handles.is_empty() {
+            return Ok(0);
+        }
         let info = ffi::syncobj::timeline_wait(
             self.as_fd(),
             bytemuck::cast_slice(handles),
@@ -1001,6 +2292,296 @@ pub trait Device: super::Device {
 
         Ok(Events::with_event_buf(event_buf, amount))
     }
+
+    /// Wait for events to become pending and receive them
+    ///
+    /// Returns an empty iterator if `timeout` elapses first, or if the wait
+    /// was interrupted by a signal. Without a timeout, this blocks until
+    /// events arrive.
+    fn wait_for_event(&self, timeout: Option<Duration>) -> io::Result<Events>
+    where
+        Self: Sized,
+    {
+        let empty = || Events::with_event_buf([0; 1024], 0);
+        let timeout_ms = match timeout {
+            // Round up, so short timeouts do not turn into busy loops
+            Some(timeout) => {
+                let ms = (timeout.as_nanos() + 999_999) / 1_000_000;
+                ms.min(i32::MAX as u128) as i32
+            }
+            None => -1,
+        };
+
+        let mut fds = [PollFd::new(self, PollFlags::IN)];
+        match rustix::event::poll(&mut fds, timeout_ms) {
+            Ok(0) | Err(Errno::INTR) => return Ok(empty()),
+            Ok(_) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        match self.receive_events() {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(empty()),
+            result => result,
+        }
+    }
+}
+
+/// Looks up a property of a resource by name, returning its info and current value.
+pub(crate) fn find_property<D, T>(
+    device: &D,
+    handle: T,
+    name: &str,
+) -> io::Result<Option<(property::Info, property::RawValue)>>
+where
+    D: Device + ?Sized,
+    T: ResourceHandle,
+{
+    let mut cache = device
+        .property_cache()
+        .map(|cache| cache.lock().unwrap_or_else(|err| err.into_inner()));
+
+    let matches = |info: &property::Info| info.name().to_bytes() == name.as_bytes();
+    for (prop, value) in device.get_properties(handle)? {
+        match cache.as_mut() {
+            Some(cache) => {
+                let info = cache.get_or_fetch(device, prop)?;
+                if matches(info) {
+                    return Ok(Some((info.clone(), value)));
+                }
+            }
+            None => {
+                let info = device.get_property(prop)?;
+                if matches(&info) {
+                    return Ok(Some((info, value)));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the modes of a connector, without fetching the rest of its information.
+fn get_connector_modes<D>(
+    device: &D,
+    handle: connector::Handle,
+    force_probe: bool,
+) -> io::Result<Vec<Mode>>
+where
+    D: Device + ?Sized,
+{
+    let mut modes = Vec::new();
+
+    let _ffi_info = ffi::mode::get_connector(
+        device.as_fd(),
+        handle.into(),
+        None,
+        None,
+        Some(&mut modes),
+        None,
+        force_probe,
+    )?;
+
+    Ok(Mode::wrap_vec(modes))
+}
+
+/// Returns a plane of the given type compatible with a crtc, preferring one
+/// that is currently bound to it.
+fn find_plane_for_crtc<D>(
+    device: &D,
+    crtc: crtc::Handle,
+    plane_type: PlaneType,
+) -> io::Result<Option<plane::Handle>>
+where
+    D: Device + ?Sized,
+{
+    let mut candidates = Vec::new();
+    for info in device.planes_for_crtc(crtc)? {
+        if device.plane_type(info.handle()).ok() == Some(plane_type) {
+            candidates.push(info);
+        }
+    }
+
+    let bound = candidates.iter().find(|info| info.crtc() == Some(crtc));
+    Ok(bound.or(candidates.first()).map(|info| info.handle()))
+}
+
+/// Returns the legacy gamma length of a crtc, failing with
+/// [`io::ErrorKind::Unsupported`] if it has none.
+///
+/// Drivers only supporting color management through the `GAMMA_LUT` property
+/// report a length of zero, which would otherwise surface as a confusing `EINVAL`.
+pub(crate) fn legacy_gamma_length<D>(device: &D, crtc: crtc::Handle) -> io::Result<usize>
+where
+    D: Device + ?Sized,
+{
+    match device.get_crtc(crtc)?.gamma_length() {
+        0 => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "crtc has no legacy gamma ramp, use the GAMMA_LUT property instead",
+        )),
+        length => Ok(length as usize),
+    }
+}
+
+/// Returns the format the kernel assumes for a legacy framebuffer of the given
+/// depth and bpp.
+fn legacy_framebuffer_format(depth: u32, bpp: u32) -> Option<DrmFourcc> {
+    // source: drm_fourcc.c, drm_driver_legacy_fb_format
+    match (bpp, depth) {
+        (8, 8) => Some(DrmFourcc::C8),
+        (16, 15) => Some(DrmFourcc::Xrgb1555),
+        (16, 16) => Some(DrmFourcc::Rgb565),
+        (24, 24) => Some(DrmFourcc::Rgb888),
+        (32, 24) => Some(DrmFourcc::Xrgb8888),
+        (32, 30) => Some(DrmFourcc::Xrgb2101010),
+        (32, 32) => Some(DrmFourcc::Argb8888),
+        _ => None,
+    }
+}
+
+/// Returns the depth and bpp to add a legacy framebuffer of `format` with.
+///
+/// Fails with `EINVAL` if the kernel would not derive `format` from them.
+fn legacy_depth_bpp(format: DrmFourcc) -> io::Result<(u32, u32)> {
+    let (depth, bpp) = buffer::FormatInfo::from_format(format)
+        .and_then(|info| Some((info.depth()?, info.bpp(0))))
+        .filter(|&(depth, bpp)| legacy_framebuffer_format(depth, bpp) == Some(format))
+        .ok_or(Errno::INVAL)?;
+    Ok((depth, bpp))
+}
+
+/// Creates a property blob holding the bytes of `data`, returning its id.
+fn create_blob<D, T>(device: &D, data: &T) -> io::Result<u64>
+where
+    D: Device + ?Sized,
+{
+    let data =
+        unsafe { std::slice::from_raw_parts_mut(data as *const _ as *mut u8, mem::size_of::<T>()) };
+    let blob = ffi::mode::create_property_blob(device.as_fd(), data)?;
+    Ok(blob.blob_id.into())
+}
+
+/// Decodes the bounds of a range property from its raw flags and values.
+///
+/// Fails with `EINVAL` if the property is not a range property.
+fn decode_range(flags: ModePropFlags, values: &[u64]) -> io::Result<(i64, i64)> {
+    match values {
+        [min, max, ..] if flags.contains(ModePropFlags::RANGE) => {
+            let min = i64::try_from(*min).map_err(|_| Errno::INVAL)?;
+            let max = i64::try_from(*max).unwrap_or(i64::MAX);
+            Ok((min, max))
+        }
+        [min, max, ..] if flags.contains(ModePropFlags::SIGNED_RANGE) => {
+            Ok((*min as i64, *max as i64))
+        }
+        _ => Err(Errno::INVAL.into()),
+    }
+}
+
+/// Maps the errors the kernel reports for unknown object ids, `ENOENT` and
+/// `EINVAL`, to `Ok(None)`.
+fn unknown_object_as_none<T>(result: io::Result<T>) -> io::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err)
+            if err.raw_os_error() == Some(Errno::NOENT.raw_os_error())
+                || err.raw_os_error() == Some(Errno::INVAL.raw_os_error()) =>
+        {
+            Ok(None)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Adds the property called `name` of a resource to an atomic request.
+///
+/// Fails with `ENOENT` if the resource has no such property.
+pub(crate) fn add_property_by_name<D, T>(
+    device: &D,
+    req: &mut atomic::AtomicModeReq,
+    handle: T,
+    name: &str,
+    value: property::RawValue,
+) -> io::Result<()>
+where
+    D: Device + ?Sized,
+    T: ResourceHandle,
+{
+    let (info, _) = find_property(device, handle, name)?.ok_or(Errno::NOENT)?;
+    req.add_raw_property(handle.into(), info.handle(), value);
+    Ok(())
+}
+
+/// Returns the name of the current value of the enum property called `name`.
+///
+/// Fails with `ENOENT` if the resource has no such property, and with `EINVAL`
+/// if the property is not an enum or has an unknown value.
+pub(crate) fn get_enum_property<D, T>(device: &D, handle: T, name: &str) -> io::Result<String>
+where
+    D: Device + ?Sized,
+    T: ResourceHandle,
+{
+    let (info, raw) = find_property(device, handle, name)?.ok_or(Errno::NOENT)?;
+    let value = info.enum_value_name(raw).ok_or(Errno::INVAL)?;
+    Ok(value.to_string_lossy().into_owned())
+}
+
+/// Sets the enum property called `name` of a resource to the value named `value`.
+///
+/// Fails with `ENOENT` if the resource has no such property, and with `EINVAL`
+/// if the property is not an enum or does not support the value.
+pub(crate) fn set_enum_property<D, T>(
+    device: &D,
+    handle: T,
+    name: &str,
+    value: &str,
+) -> io::Result<()>
+where
+    D: Device + ?Sized,
+    T: ResourceHandle,
+{
+    let (info, _) = find_property(device, handle, name)?.ok_or(Errno::NOENT)?;
+    let raw = info.enum_value_by_name(value).ok_or(Errno::INVAL)?;
+    device.set_property(handle, info.handle(), raw)
+}
+
+/// A property blob that is destroyed when dropped
+///
+/// Created by [`Device::create_property_blob_owned`]. Blobs created for every
+/// commit, e.g. for the `MODE_ID` property, are easily leaked otherwise.
+/// Note that a blob may be destroyed while it is still in use by a committed
+/// property, as the kernel keeps its own reference.
+#[derive(Debug)]
+pub struct PropertyBlob<'a, D: Device + ?Sized> {
+    device: &'a D,
+    id: u64,
+}
+
+impl<'a, D: Device + ?Sized> PropertyBlob<'a, D> {
+    /// Returns the id of this blob
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the property value referring to this blob
+    pub fn value(&self) -> property::Value<'static> {
+        property::Value::Blob(self.id)
+    }
+
+    /// Releases ownership of the blob without destroying it
+    ///
+    /// The blob then has to be destroyed using [`Device::destroy_property_blob`].
+    pub fn into_value(self) -> property::Value<'static> {
+        let value = self.value();
+        mem::forget(self);
+        value
+    }
+}
+
+impl<'a, D: Device + ?Sized> Drop for PropertyBlob<'a, D> {
+    fn drop(&mut self) {
+        let _ = self.device.destroy_property_blob(self.id);
+    }
 }
 
 /// List of leased resources
@@ -1070,6 +2651,20 @@ bitflags::bitflags! {
     }
 }
 
+/// Annotation describing how the dirty regions of a framebuffer changed
+///
+/// These represent the [`ffi::drm_sys::DRM_MODE_FB_DIRTY_FLAGS`] bits wrapped
+/// in a regular `enum` due to their mutual-exclusiveness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DirtyAnnotation {
+    /// The regions changed in an unspecified way
+    None,
+    /// The regions were copied from other regions of the same framebuffer
+    Copy,
+    /// The regions were filled with a single color
+    Fill(u32),
+}
+
 /// Target to alter the sequence of page flips
 ///
 /// These represent the [`ffi::drm_sys::DRM_MODE_PAGE_FLIP_TARGET`] bits
@@ -1084,10 +2679,19 @@ pub enum PageFlipTarget {
 }
 
 /// Iterator over [`Event`]s of a device. Create via [`Device::receive_events()`].
+///
+/// Page flip events carry the crtc they belong to only if the driver supports
+/// the [`CRTCInVBlankEvent`](crate::DriverCapability::CRTCInVBlankEvent)
+/// capability. Otherwise the crtc is taken from the user data, which
+/// [`Device::page_flip`] sets to the raw crtc id. Unless told otherwise using
+/// [`Events::with_crtc_in_event`], the crtc id is used if it is non-zero, falling
+/// back to the user data. Events whose crtc cannot be determined are returned
+/// as [`Event::Unknown`].
 pub struct Events {
     event_buf: [u8; 1024],
     amount: usize,
     i: usize,
+    crtc_in_event: Option<bool>,
 }
 
 impl Events {
@@ -1098,7 +2702,103 @@ impl Events {
             event_buf,
             amount,
             i: 0,
+            crtc_in_event: None,
+        }
+    }
+
+    /// Sets whether the driver supports the
+    /// [`CRTCInVBlankEvent`](crate::DriverCapability::CRTCInVBlankEvent)
+    /// capability, instead of guessing from the contents of each event.
+    pub fn with_crtc_in_event(mut self, crtc_in_event: bool) -> Self {
+        self.crtc_in_event = Some(crtc_in_event);
+        self
+    }
+}
+
+// Events are handed to other threads for processing
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Events>();
+};
+
+/// Reads [`Event`]s from a device, for use with an event loop.
+///
+/// The file descriptor of the device, also available from [`EventReader`]
+/// through [`AsFd`], becomes readable once events are pending. When it is
+/// registered with `poll`, `epoll` or an event loop built on top of them,
+/// [`EventReader::poll_events`] can be called whenever it is readable.
+///
+/// If the device was opened non-blocking, reading without pending events
+/// returns an empty iterator instead of failing with `EAGAIN`. Incomplete
+/// events, which are never returned by the kernel but may be returned by other
+/// sources of events, are retained until the next read completes them.
+///
+/// The reader queries the
+/// [`CRTCInVBlankEvent`](crate::DriverCapability::CRTCInVBlankEvent)
+/// capability once on creation, to decode events without guessing, see [`Events`].
+pub struct EventReader<'a, D: Device + ?Sized> {
+    device: &'a D,
+    pending: Vec<u8>,
+    crtc_in_event: Option<bool>,
+}
+
+impl<'a, D: Device + ?Sized> EventReader<'a, D> {
+    /// Creates a new reader for the events of `device`
+    pub fn new(device: &'a D) -> Self {
+        let crtc_in_event = device
+            .has_capability(crate::DriverCapability::CRTCInVBlankEvent)
+            .ok();
+        EventReader {
+            device,
+            pending: Vec::new(),
+            crtc_in_event,
+        }
+    }
+
+    /// Reads all currently available events
+    pub fn poll_events(&mut self) -> io::Result<Events> {
+        let mut event_buf: [u8; 1024] = [0; 1024];
+        let retained = self.pending.len();
+        event_buf[..retained].copy_from_slice(&self.pending);
+
+        let mut amount = match rustix::io::read(self.device.as_fd(), &mut event_buf[retained..]) {
+            Ok(amount) => retained + amount,
+            Err(Errno::AGAIN) => retained,
+            Err(err) => return Err(err.into()),
+        };
+
+        // Only hand out complete events
+        let header_len = mem::size_of::<ffi::drm_event>();
+        let mut complete = 0;
+        while complete + header_len <= amount {
+            let event = unsafe {
+                std::ptr::read_unaligned(event_buf.as_ptr().add(complete) as *const ffi::drm_event)
+            };
+            let length = event.length as usize;
+            if length < header_len {
+                // Malformed, discard the remaining data
+                amount = complete;
+                break;
+            }
+            if complete + length > amount {
+                break;
+            }
+            complete += length;
         }
+
+        self.pending.clear();
+        self.pending.extend_from_slice(&event_buf[complete..amount]);
+        let events = Events::with_event_buf(event_buf, complete);
+        Ok(match self.crtc_in_event {
+            Some(crtc_in_event) => events.with_crtc_in_event(crtc_in_event),
+            None => events,
+        })
+    }
+}
+
+impl<'a, D: Device + ?Sized> AsFd for EventReader<'a, D> {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.device.as_fd()
     }
 }
 
@@ -1132,6 +2832,10 @@ pub struct PageFlipEvent {
     pub duration: Duration,
     /// crtc that did throw the event
     pub crtc: crtc::Handle,
+    /// user data that was passed to
+    /// [`Device::atomic_commit_with_user_data`], or the raw crtc id for flips
+    /// requested by [`Device::page_flip`]
+    pub user_data: u64,
 }
 
 impl Iterator for Events {
@@ -1142,19 +2846,26 @@ impl Iterator for Events {
             let event_ptr = unsafe { self.event_buf.as_ptr().add(self.i) as *const ffi::drm_event };
             let event = unsafe { std::ptr::read_unaligned(event_ptr) };
             self.i += event.length as usize;
+            let raw_event = || {
+                Event::Unknown(self.event_buf[self.i - (event.length as usize)..self.i].to_vec())
+            };
             match event.type_ {
                 ffi::DRM_EVENT_VBLANK => {
                     let vblank_event = unsafe {
                         std::ptr::read_unaligned(event_ptr as *const ffi::drm_event_vblank)
                     };
+                    #[allow(clippy::unnecessary_cast)]
+                    let crtc = match from_u32(vblank_event.crtc_id as u32) {
+                        Some(crtc) => crtc,
+                        None => return Some(raw_event()),
+                    };
                     Some(Event::Vblank(VblankEvent {
                         frame: vblank_event.sequence,
                         time: Duration::new(
                             vblank_event.tv_sec as u64,
                             vblank_event.tv_usec * 1000,
                         ),
-                        #[allow(clippy::unnecessary_cast)]
-                        crtc: from_u32(vblank_event.crtc_id as u32).unwrap(),
+                        crtc,
                         user_data: vblank_event.user_data as usize,
                     }))
                 }
@@ -1162,23 +2873,27 @@ impl Iterator for Events {
                     let vblank_event = unsafe {
                         std::ptr::read_unaligned(event_ptr as *const ffi::drm_event_vblank)
                     };
+                    let crtc_in_event = self.crtc_in_event.unwrap_or(vblank_event.crtc_id != 0);
+                    let crtc_id = if crtc_in_event {
+                        vblank_event.crtc_id
+                    } else {
+                        vblank_event.user_data as u32
+                    };
+                    let crtc = match from_u32(crtc_id) {
+                        Some(crtc) => crtc,
+                        None => return Some(raw_event()),
+                    };
                     Some(Event::PageFlip(PageFlipEvent {
                         frame: vblank_event.sequence,
                         duration: Duration::new(
                             vblank_event.tv_sec as u64,
                             vblank_event.tv_usec * 1000,
                         ),
-                        crtc: from_u32(if vblank_event.crtc_id != 0 {
-                            vblank_event.crtc_id
-                        } else {
-                            vblank_event.user_data as u32
-                        })
-                        .unwrap(),
+                        crtc,
+                        user_data: vblank_event.user_data,
                     }))
                 }
-                _ => Some(Event::Unknown(
-                    self.event_buf[self.i - (event.length as usize)..self.i].to_vec(),
-                )),
+                _ => Some(raw_event()),
             }
         } else {
             None
@@ -1188,7 +2903,10 @@ impl Iterator for Events {
 
 /// The set of [`ResourceHandles`] that a
 /// [`Device`] exposes. Excluding Plane resources.
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+///
+/// The default value is empty and can be filled using
+/// [`Device::resource_handles_into`].
+#[derive(Debug, Clone, Default, Hash, PartialEq, Eq)]
 pub struct ResourceHandles {
     /// Set of [`framebuffer::Handle`]
     pub fbs: Vec<framebuffer::Handle>,
@@ -1219,6 +2937,13 @@ impl ResourceHandles {
     }
 
     /// Returns the set of [`framebuffer::Handle`]
+    ///
+    /// The kernel only reports framebuffers created through the same open file,
+    /// whether or not it is the DRM master. An empty list therefore does not
+    /// mean that no framebuffers exist: framebuffers of other clients, such as
+    /// the one left on screen by a previous master, are never listed. Use
+    /// [`Device::get_crtc`] or [`Device::get_plane`] to find the framebuffers
+    /// currently being scanned out.
     pub fn framebuffers(&self) -> &[framebuffer::Handle] {
         &self.fbs
     }
@@ -1233,6 +2958,83 @@ impl ResourceHandles {
         self.height.0..=self.height.1
     }
 
+    /// Returns the supported minimum width for framebuffers
+    pub fn min_width(&self) -> u32 {
+        self.width.0
+    }
+
+    /// Returns the supported maximum width for framebuffers
+    pub fn max_width(&self) -> u32 {
+        self.width.1
+    }
+
+    /// Returns the supported minimum height for framebuffers
+    pub fn min_height(&self) -> u32 {
+        self.height.0
+    }
+
+    /// Returns the supported maximum height for framebuffers
+    pub fn max_height(&self) -> u32 {
+        self.height.1
+    }
+
+    /// Returns an iterator over all handles of these resources, in the order
+    /// framebuffers, crtcs, connectors and encoders.
+    pub fn all_handles(&self) -> impl Iterator<Item = RawResourceHandle> + '_ {
+        let fbs = self
+            .fbs
+            .iter()
+            .map(|&handle| RawResourceHandle::from(handle));
+        let crtcs = self
+            .crtcs
+            .iter()
+            .map(|&handle| RawResourceHandle::from(handle));
+        let connectors = self
+            .connectors
+            .iter()
+            .map(|&handle| RawResourceHandle::from(handle));
+        let encoders = self
+            .encoders
+            .iter()
+            .map(|&handle| RawResourceHandle::from(handle));
+        fbs.chain(crtcs).chain(connectors).chain(encoders)
+    }
+
+    /// Returns information about every connector of these resources that is
+    /// currently connected to a display.
+    ///
+    /// See [`Device::get_connector`] for the meaning of `force_probe`.
+    pub fn connected_connectors(
+        &self,
+        device: &impl Device,
+        force_probe: bool,
+    ) -> io::Result<Vec<connector::Info>> {
+        let mut connected = Vec::new();
+        for &handle in self.connectors.iter() {
+            let info = device.get_connector(handle, force_probe)?;
+            if info.state() == connector::State::Connected {
+                connected.push(info);
+            }
+        }
+        Ok(connected)
+    }
+
+    /// Returns the index of a crtc within [`ResourceHandles::crtcs`].
+    ///
+    /// This index is used by vblank waits and the bitmasks of
+    /// [`CrtcListFilter`], which refer to crtcs by index rather than handle.
+    pub fn crtc_index(&self, crtc: crtc::Handle) -> Option<u32> {
+        self.crtcs
+            .iter()
+            .position(|&c| c == crtc)
+            .map(|index| index as u32)
+    }
+
+    /// Returns the crtc at the given index, as returned by [`ResourceHandles::crtc_index`].
+    pub fn crtc_at(&self, index: u32) -> Option<crtc::Handle> {
+        self.crtcs.get(index as usize).copied()
+    }
+
     /// Apply a filter the all crtcs of these resources, resulting in a list of crtcs allowed.
     pub fn filter_crtcs(&self, filter: CrtcListFilter) -> Vec<crtc::Handle> {
         self.crtcs
@@ -1260,6 +3062,48 @@ pub struct Mode {
 }
 
 impl Mode {
+    /// Creates a mode from raw timings, e.g. taken from an EDID detailed
+    /// timing descriptor.
+    ///
+    /// `name` is truncated to 31 bytes. Skew and scan are left at 0.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &str,
+        clock: u32,
+        hdisplay: u16,
+        hsync_start: u16,
+        hsync_end: u16,
+        htotal: u16,
+        vdisplay: u16,
+        vsync_start: u16,
+        vsync_end: u16,
+        vtotal: u16,
+        vrefresh: u32,
+        flags: ModeFlags,
+        mode_type: ModeTypeFlags,
+    ) -> Mode {
+        let mut mode = ffi::drm_mode_modeinfo {
+            clock,
+            hdisplay,
+            hsync_start,
+            hsync_end,
+            htotal,
+            vdisplay,
+            vsync_start,
+            vsync_end,
+            vtotal,
+            vrefresh,
+            flags: flags.bits(),
+            type_: mode_type.bits(),
+            ..Default::default()
+        };
+        let len = name.len().min(mode.name.len() - 1);
+        for (dst, &src) in mode.name.iter_mut().zip(&name.as_bytes()[..len]) {
+            *dst = src as _;
+        }
+        Mode { mode }
+    }
+
     /// Returns the name of this mode.
     pub fn name(&self) -> &std::ffi::CStr {
         unsafe { std::ffi::CStr::from_ptr(&self.mode.name[0] as _) }
@@ -1339,6 +3183,80 @@ impl fmt::Debug for Mode {
     }
 }
 
+/// Serialized form of a [`Mode`], listing its timings by name instead of
+/// exposing the layout of the raw struct.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ModeTimings {
+    name: String,
+    clock: u32,
+    hdisplay: u16,
+    hsync_start: u16,
+    hsync_end: u16,
+    htotal: u16,
+    hskew: u16,
+    vdisplay: u16,
+    vsync_start: u16,
+    vsync_end: u16,
+    vtotal: u16,
+    vscan: u16,
+    vrefresh: u32,
+    flags: u32,
+    mode_type: u32,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Mode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mode = &self.mode;
+        ModeTimings {
+            name: self.name().to_string_lossy().into_owned(),
+            clock: mode.clock,
+            hdisplay: mode.hdisplay,
+            hsync_start: mode.hsync_start,
+            hsync_end: mode.hsync_end,
+            htotal: mode.htotal,
+            hskew: mode.hskew,
+            vdisplay: mode.vdisplay,
+            vsync_start: mode.vsync_start,
+            vsync_end: mode.vsync_end,
+            vtotal: mode.vtotal,
+            vscan: mode.vscan,
+            vrefresh: mode.vrefresh,
+            flags: mode.flags,
+            mode_type: mode.type_,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Mode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let timings = ModeTimings::deserialize(deserializer)?;
+        let mut mode = Mode::new(
+            &timings.name,
+            timings.clock,
+            timings.hdisplay,
+            timings.hsync_start,
+            timings.hsync_end,
+            timings.htotal,
+            timings.vdisplay,
+            timings.vsync_start,
+            timings.vsync_end,
+            timings.vtotal,
+            timings.vrefresh,
+            ModeFlags::empty(),
+            ModeTypeFlags::empty(),
+        );
+        mode.mode.hskew = timings.hskew;
+        mode.mode.vscan = timings.vscan;
+        mode.mode.flags = timings.flags;
+        mode.mode.type_ = timings.mode_type;
+        Ok(mode)
+    }
+}
+
 bitflags::bitflags! {
     /// Display mode type flags
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -1431,6 +3349,162 @@ pub enum PlaneType {
     Cursor = ffi::DRM_PLANE_TYPE_CURSOR,
 }
 
+/// Filter used to scale the contents of a plane or crtc, as controlled by the
+/// `SCALING_FILTER` property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum ScalingFilter {
+    /// Filter chosen by the driver
+    Default,
+    /// Nearest neighbor filtering, for sharp integer scaling
+    NearestNeighbor,
+}
+
+impl ScalingFilter {
+    /// Get the name the kernel uses for this value of the `SCALING_FILTER` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_crtc.c, drm_create_scaling_filter_prop
+        match self {
+            ScalingFilter::Default => "Default",
+            ScalingFilter::NearestNeighbor => "Nearest Neighbor",
+        }
+    }
+
+    /// Get the value from its kernel name, as returned by [`ScalingFilter::as_str`]
+    pub fn from_name(name: &str) -> Option<ScalingFilter> {
+        [ScalingFilter::Default, ScalingFilter::NearestNeighbor]
+            .into_iter()
+            .find(|value| value.as_str() == name)
+    }
+}
+
+/// Type of a modesetting object, as used by the kernel to tag object ids
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectType {
+    /// Any type of object
+    Any,
+    /// A CRTC object
+    CRTC,
+    /// A Connector object
+    Connector,
+    /// An Encoder object
+    Encoder,
+    /// A Mode object
+    Mode,
+    /// A Property object
+    Property,
+    /// A Framebuffer object
+    Framebuffer,
+    /// A property blob object
+    Blob,
+    /// A Plane object
+    Plane,
+    /// An object type unknown to drm-rs
+    Unknown(u32),
+}
+
+impl From<u32> for ObjectType {
+    fn from(raw: u32) -> Self {
+        match raw {
+            ffi::DRM_MODE_OBJECT_ANY => ObjectType::Any,
+            ffi::DRM_MODE_OBJECT_CRTC => ObjectType::CRTC,
+            ffi::DRM_MODE_OBJECT_CONNECTOR => ObjectType::Connector,
+            ffi::DRM_MODE_OBJECT_ENCODER => ObjectType::Encoder,
+            ffi::DRM_MODE_OBJECT_MODE => ObjectType::Mode,
+            ffi::DRM_MODE_OBJECT_PROPERTY => ObjectType::Property,
+            ffi::DRM_MODE_OBJECT_FB => ObjectType::Framebuffer,
+            ffi::DRM_MODE_OBJECT_BLOB => ObjectType::Blob,
+            ffi::DRM_MODE_OBJECT_PLANE => ObjectType::Plane,
+            raw => ObjectType::Unknown(raw),
+        }
+    }
+}
+
+impl From<ObjectType> for u32 {
+    fn from(object_type: ObjectType) -> Self {
+        match object_type {
+            ObjectType::Any => ffi::DRM_MODE_OBJECT_ANY,
+            ObjectType::CRTC => ffi::DRM_MODE_OBJECT_CRTC,
+            ObjectType::Connector => ffi::DRM_MODE_OBJECT_CONNECTOR,
+            ObjectType::Encoder => ffi::DRM_MODE_OBJECT_ENCODER,
+            ObjectType::Mode => ffi::DRM_MODE_OBJECT_MODE,
+            ObjectType::Property => ffi::DRM_MODE_OBJECT_PROPERTY,
+            ObjectType::Framebuffer => ffi::DRM_MODE_OBJECT_FB,
+            ObjectType::Blob => ffi::DRM_MODE_OBJECT_BLOB,
+            ObjectType::Plane => ffi::DRM_MODE_OBJECT_PLANE,
+            ObjectType::Unknown(raw) => raw,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// Rotation and reflection of a plane, as controlled by the `rotation`
+    /// plane property
+    ///
+    /// Rotations are counter-clockwise. Exactly one rotation should be set,
+    /// optionally combined with reflections.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct Rotation : u32 {
+        /// No rotation
+        const ROTATE_0 = ffi::DRM_MODE_ROTATE_0;
+        /// Rotate by 90 degrees
+        const ROTATE_90 = ffi::DRM_MODE_ROTATE_90;
+        /// Rotate by 180 degrees
+        const ROTATE_180 = ffi::DRM_MODE_ROTATE_180;
+        /// Rotate by 270 degrees
+        const ROTATE_270 = ffi::DRM_MODE_ROTATE_270;
+        /// Reflect along the x axis
+        const REFLECT_X = ffi::DRM_MODE_REFLECT_X;
+        /// Reflect along the y axis
+        const REFLECT_Y = ffi::DRM_MODE_REFLECT_Y;
+    }
+}
+
+/// Cache of [`property::Info`]s, avoiding repeated [`Device::get_property`] calls.
+///
+/// Property information does not change for the lifetime of a device, so it
+/// can be fetched once and reused, e.g. when building a commit every frame.
+/// A cache must only be used with a single device, as property handles are
+/// not unique across devices. The property helpers of [`Device`] use the
+/// cache returned by [`Device::property_cache`].
+#[derive(Debug, Clone, Default)]
+pub struct PropertyCache {
+    infos: HashMap<property::Handle, property::Info>,
+}
+
+impl PropertyCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the information about a property, fetching it from the device
+    /// if it is not cached yet.
+    pub fn get_or_fetch<D: Device + ?Sized>(
+        &mut self,
+        device: &D,
+        handle: property::Handle,
+    ) -> io::Result<&property::Info> {
+        let info = match self.infos.entry(handle) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(device.get_property(handle)?)
+            }
+        };
+        Ok(info)
+    }
+
+    /// Returns the cached information about a property, if any
+    pub fn get(&self, handle: property::Handle) -> Option<&property::Info> {
+        self.infos.get(&handle)
+    }
+
+    /// Removes all cached information
+    pub fn clear(&mut self) {
+        self.infos.clear();
+    }
+}
+
 /// Wrapper around a set of property IDs and their raw values.
 #[derive(Debug, Clone)]
 pub struct PropertyValueSet {
@@ -1513,6 +3587,60 @@ impl ClipRect {
     }
 }
 
+/// Destination rectangle of a plane on its crtc, in pixels
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct CrtcRect {
+    /// X coordinate of the top left corner, may be negative or off-screen
+    pub x: i32,
+    /// Y coordinate of the top left corner, may be negative or off-screen
+    pub y: i32,
+    /// Width of the rectangle
+    pub w: u32,
+    /// Height of the rectangle
+    pub h: u32,
+}
+
+impl CrtcRect {
+    /// Create a new destination rectangle.
+    pub fn new(x: i32, y: i32, w: u32, h: u32) -> Self {
+        CrtcRect { x, y, w, h }
+    }
+}
+
+/// Source rectangle of a plane within its framebuffer, in 16.16 fixed point
+///
+/// Use [`SrcRect::from_pixels`] to create a rectangle from integer coordinates.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SrcRect {
+    /// X coordinate of the top left corner, in 16.16 fixed point
+    pub x: u32,
+    /// Y coordinate of the top left corner, in 16.16 fixed point
+    pub y: u32,
+    /// Width of the rectangle, in 16.16 fixed point
+    pub w: u32,
+    /// Height of the rectangle, in 16.16 fixed point
+    pub h: u32,
+}
+
+impl SrcRect {
+    /// Create a new source rectangle from 16.16 fixed point values.
+    pub fn new(x: u32, y: u32, w: u32, h: u32) -> Self {
+        SrcRect { x, y, w, h }
+    }
+
+    /// Create a new source rectangle from whole pixels.
+    ///
+    /// Values must fit into 16 bits.
+    pub fn from_pixels(x: u16, y: u16, w: u16, h: u16) -> Self {
+        SrcRect {
+            x: (x as u32) << 16,
+            y: (y as u32) << 16,
+            w: (w as u32) << 16,
+            h: (h as u32) << 16,
+        }
+    }
+}
+
 bitflags::bitflags! {
     /// Commit flags for atomic mode setting
     ///
@@ -1582,3 +3710,693 @@ bitflags::bitflags! {
         const MODIFIERS = ffi::DRM_MODE_FB_MODIFIERS;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(super) fn handle<T: From<RawResourceHandle>>(id: u32) -> T {
+        RawResourceHandle::new(id).unwrap().into()
+    }
+
+    /// Records destroyed property blobs instead of issuing ioctls
+    pub(super) struct BlobCounter {
+        mock: crate::mock::MockDevice,
+        pub(super) destroyed: std::cell::RefCell<Vec<u64>>,
+    }
+
+    impl AsFd for BlobCounter {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for BlobCounter {}
+    impl Device for BlobCounter {
+        fn destroy_property_blob(&self, blob: u64) -> io::Result<()> {
+            self.destroyed.borrow_mut().push(blob);
+            Ok(())
+        }
+    }
+
+    pub(super) fn blob_counter() -> BlobCounter {
+        BlobCounter {
+            mock: crate::mock::MockDevice::new().unwrap(),
+            destroyed: Default::default(),
+        }
+    }
+
+    #[test]
+    fn property_blob_drop_destroys_once() {
+        let device = blob_counter();
+        let blob = PropertyBlob {
+            device: &device,
+            id: 7,
+        };
+        assert_eq!(blob.value(), property::Value::Blob(7));
+        drop(blob);
+        assert_eq!(*device.destroyed.borrow(), [7]);
+    }
+
+    #[test]
+    fn property_blob_into_value_defuses() {
+        let device = blob_counter();
+        let blob = PropertyBlob {
+            device: &device,
+            id: 8,
+        };
+        assert_eq!(blob.into_value(), property::Value::Blob(8));
+        assert!(device.destroyed.borrow().is_empty());
+    }
+
+    #[test]
+    fn page_flip_event_user_data() {
+        let device = crate::mock::MockDevice::new().unwrap();
+        device.push_page_flip_event(handle(5), 1, 9).unwrap();
+        device.push_page_flip_event(handle(5), 2, 9).unwrap();
+
+        let mut events = device.receive_events().unwrap();
+        match events.next() {
+            Some(Event::PageFlip(event)) => {
+                assert_eq!(event.crtc, handle(5));
+                assert_eq!(event.user_data, 9);
+            }
+            _ => panic!("expected a page flip event"),
+        }
+
+        // Without CRTCInVBlankEvent, the crtc is taken from the user data
+        let mut events = events.with_crtc_in_event(false);
+        match events.next() {
+            Some(Event::PageFlip(event)) => {
+                assert_eq!(event.crtc, handle(9));
+                assert_eq!(event.user_data, 9);
+            }
+            _ => panic!("expected a page flip event"),
+        }
+    }
+
+    #[test]
+    fn crtc_index() {
+        let res = ResourceHandles {
+            crtcs: vec![handle(40), handle(52), handle(64)],
+            ..Default::default()
+        };
+        assert_eq!(res.crtc_index(handle(40)), Some(0));
+        assert_eq!(res.crtc_index(handle(64)), Some(2));
+        assert_eq!(res.crtc_index(handle(41)), None);
+        assert_eq!(res.crtc_at(1), Some(handle(52)));
+        assert_eq!(res.crtc_at(3), None);
+    }
+
+    /// Serves fake properties and counts the property info lookups
+    pub(super) struct PropertyCounter {
+        mock: crate::mock::MockDevice,
+        cache: Option<Mutex<PropertyCache>>,
+        /// Object id, property id, name and value of each property
+        props: Vec<(u32, u32, &'static str, u64)>,
+        pub(super) lookups: std::cell::Cell<usize>,
+        commits: std::cell::RefCell<Vec<atomic::AtomicModeReq>>,
+    }
+
+    impl AsFd for PropertyCounter {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for PropertyCounter {}
+    impl Device for PropertyCounter {
+        fn get_properties<T: ResourceHandle>(&self, handle: T) -> io::Result<PropertyValueSet> {
+            let object: u32 = handle.into();
+            let props = self.props.iter().filter(|prop| prop.0 == object);
+            Ok(PropertyValueSet {
+                prop_ids: props.clone().map(|prop| self::handle(prop.1)).collect(),
+                prop_vals: props.map(|prop| prop.3).collect(),
+            })
+        }
+
+        fn get_property(&self, handle: property::Handle) -> io::Result<property::Info> {
+            self.lookups.set(self.lookups.get() + 1);
+            let name = self
+                .props
+                .iter()
+                .find(|prop| prop.1 == u32::from(handle))
+                .ok_or(Errno::NOENT)?
+                .2;
+            let mut info = ffi::drm_mode_get_property {
+                prop_id: handle.into(),
+                ..Default::default()
+            };
+            for (dst, &src) in info.name.iter_mut().zip(name.as_bytes()) {
+                *dst = src as _;
+            }
+            Ok(property::Info {
+                handle,
+                val_type: property::ValueType::Unknown,
+                mutable: true,
+                atomic: false,
+                info,
+            })
+        }
+
+        fn property_cache(&self) -> Option<&Mutex<PropertyCache>> {
+            self.cache.as_ref()
+        }
+
+        fn atomic_commit(
+            &self,
+            _flags: AtomicCommitFlags,
+            req: atomic::AtomicModeReq,
+        ) -> io::Result<()> {
+            self.commits.borrow_mut().push(req);
+            Ok(())
+        }
+    }
+
+    /// Returns a device with a plane `3` having the properties `zpos` and
+    /// `alpha`, a connector `30` driven by crtc `40` with color management
+    /// properties, and a disabled connector `31`.
+    pub(super) fn property_counter(cache: Option<PropertyCache>) -> PropertyCounter {
+        PropertyCounter {
+            mock: crate::mock::MockDevice::new().unwrap(),
+            cache: cache.map(Mutex::new),
+            props: vec![
+                (3, 1, "zpos", 10),
+                (3, 2, "alpha", 20),
+                (30, 5, "CRTC_ID", 40),
+                (31, 5, "CRTC_ID", 0),
+                (40, 6, "ACTIVE", 1),
+                (40, 10, "DEGAMMA_LUT", 50),
+                (40, 11, "CTM", 51),
+                (40, 12, "GAMMA_LUT", 52),
+            ],
+            lookups: Default::default(),
+            commits: Default::default(),
+        }
+    }
+
+    #[test]
+    fn find_property_uncached() {
+        let device = property_counter(None);
+        let plane: plane::Handle = handle(3);
+
+        let (info, value) = find_property(&device, plane, "alpha").unwrap().unwrap();
+        assert_eq!(info.handle(), handle(2));
+        assert_eq!(value, 20);
+        assert_eq!(device.lookups.get(), 2);
+
+        assert!(find_property(&device, plane, "rotation").unwrap().is_none());
+        assert_eq!(device.lookups.get(), 4);
+    }
+
+    #[test]
+    fn find_property_cached() {
+        let device = property_counter(Some(PropertyCache::new()));
+        let plane: plane::Handle = handle(3);
+
+        let (info, value) = find_property(&device, plane, "zpos").unwrap().unwrap();
+        assert_eq!(info.handle(), handle(1));
+        assert_eq!(value, 10);
+        assert_eq!(device.lookups.get(), 1);
+
+        let (info, value) = find_property(&device, plane, "alpha").unwrap().unwrap();
+        assert_eq!(info.handle(), handle(2));
+        assert_eq!(value, 20);
+        assert_eq!(device.lookups.get(), 2);
+
+        // Every property is only fetched once
+        assert!(find_property(&device, plane, "rotation").unwrap().is_none());
+        assert!(find_property(&device, plane, "alpha").unwrap().is_some());
+        assert_eq!(device.lookups.get(), 2);
+    }
+
+    #[test]
+    fn decode_ranges() {
+        // Unsigned ranges of (0, 1) are not mistaken for booleans
+        assert_eq!(decode_range(ModePropFlags::RANGE, &[0, 1]).unwrap(), (0, 1));
+        assert_eq!(decode_range(ModePropFlags::RANGE, &[2, 2]).unwrap(), (2, 2));
+        assert_eq!(
+            decode_range(ModePropFlags::RANGE, &[0, u64::MAX]).unwrap(),
+            (0, i64::MAX)
+        );
+        assert_eq!(
+            decode_range(ModePropFlags::SIGNED_RANGE, &[-4i64 as u64, 3]).unwrap(),
+            (-4, 3)
+        );
+    }
+
+    #[test]
+    fn decode_non_ranges() {
+        assert!(decode_range(ModePropFlags::ENUM, &[0, 1]).is_err());
+        assert!(decode_range(ModePropFlags::BITMASK, &[0, 1]).is_err());
+        assert!(decode_range(ModePropFlags::RANGE, &[0]).is_err());
+    }
+
+    /// Imports dma-bufs as the handle of their fd and records closed handles
+    struct ImportRecorder {
+        mock: crate::mock::MockDevice,
+        closed: std::cell::RefCell<Vec<buffer::Handle>>,
+    }
+
+    impl AsFd for ImportRecorder {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for ImportRecorder {}
+    impl Device for ImportRecorder {
+        fn prime_fd_to_buffer(&self, fd: BorrowedFd<'_>) -> io::Result<buffer::Handle> {
+            Ok(handle(std::os::unix::io::AsRawFd::as_raw_fd(&fd) as u32))
+        }
+
+        fn close_buffer(&self, handle: buffer::Handle) -> io::Result<()> {
+            self.closed.borrow_mut().push(handle);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dmabuf_import_closes_handles_on_error() {
+        let device = ImportRecorder {
+            mock: crate::mock::MockDevice::new().unwrap(),
+            closed: Default::default(),
+        };
+        let fd = device.as_fd();
+        let imported: buffer::Handle = handle(std::os::unix::io::AsRawFd::as_raw_fd(&fd) as u32);
+
+        // The mock device fails to create the framebuffer
+        let err = device
+            .add_framebuffer_from_dmabuf(
+                &[fd, fd],
+                &[],
+                DrmFourcc::Nv12,
+                None,
+                (1920, 1080),
+                &[1920, 1920],
+                &[0, 1920 * 1080],
+                FbCmd2Flags::empty(),
+            )
+            .unwrap_err();
+        assert_ne!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+        assert_eq!(*device.closed.borrow(), [imported]);
+
+        // Handles the caller already held are left alone
+        device.closed.borrow_mut().clear();
+        device
+            .add_framebuffer_from_dmabuf(
+                &[fd, fd],
+                &[imported],
+                DrmFourcc::Nv12,
+                None,
+                (1920, 1080),
+                &[1920, 1920],
+                &[0, 1920 * 1080],
+                FbCmd2Flags::empty(),
+            )
+            .unwrap_err();
+        assert!(device.closed.borrow().is_empty());
+
+        let err = device
+            .add_framebuffer_from_dmabuf(
+                &[fd, fd],
+                &[],
+                DrmFourcc::Nv12,
+                None,
+                (1920, 1080),
+                &[1920],
+                &[0, 1920 * 1080],
+                FbCmd2Flags::empty(),
+            )
+            .unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+        assert!(device.closed.borrow().is_empty());
+    }
+
+    #[test]
+    fn mode_round_trip() {
+        let mode = Mode::new(
+            "1920x1080",
+            148500,
+            1920,
+            2008,
+            2052,
+            2200,
+            1080,
+            1084,
+            1089,
+            1125,
+            60,
+            ModeFlags::PHSYNC | ModeFlags::PVSYNC,
+            ModeTypeFlags::DRIVER | ModeTypeFlags::PREFERRED,
+        );
+        assert_eq!(mode.name().to_bytes(), b"1920x1080");
+        assert_eq!(mode.clock(), 148500);
+        assert_eq!(mode.size(), (1920, 1080));
+        assert_eq!(mode.hsync(), (2008, 2052, 2200));
+        assert_eq!(mode.vsync(), (1084, 1089, 1125));
+        assert_eq!(mode.hskew(), 0);
+        assert_eq!(mode.vscan(), 0);
+        assert_eq!(mode.vrefresh(), 60);
+        assert_eq!(mode.flags(), ModeFlags::PHSYNC | ModeFlags::PVSYNC);
+        assert_eq!(
+            mode.mode_type(),
+            ModeTypeFlags::DRIVER | ModeTypeFlags::PREFERRED
+        );
+    }
+
+    #[test]
+    fn mode_name_truncated() {
+        let name = "x".repeat(40);
+        let mode = Mode::new(
+            &name,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            ModeFlags::empty(),
+            ModeTypeFlags::empty(),
+        );
+        // The name keeps its terminating NUL
+        assert_eq!(mode.name().to_bytes(), &name.as_bytes()[..31]);
+    }
+
+    #[test]
+    fn reset_crtc_color_commits_once() {
+        let device = property_counter(None);
+        device.reset_crtc_color(handle(40)).unwrap();
+
+        let commits = device.commits.borrow();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].objects, [handle(40)]);
+        assert_eq!(commits[0].props, [handle(10), handle(11), handle(12)]);
+        assert_eq!(commits[0].values, [0, 0, 0]);
+    }
+
+    #[test]
+    fn src_rect_fixed_point() {
+        let rect = SrcRect::from_pixels(16, 32, 1920, 1080);
+        assert_eq!(
+            rect,
+            SrcRect::new(16 << 16, 32 << 16, 1920 << 16, 1080 << 16)
+        );
+        assert_eq!(rect.w, 0x0780_0000);
+
+        let rect = SrcRect::from_pixels(0, 0, u16::MAX, u16::MAX);
+        assert_eq!(rect.w, 0xffff_0000);
+        assert_eq!(rect.h >> 16, u16::MAX as u32);
+        assert_eq!(rect.h & 0xffff, 0);
+    }
+
+    #[test]
+    fn resource_handles_into_error() {
+        // The mock device fails to query the resources
+        let device = crate::mock::MockDevice::new().unwrap();
+        let mut res = ResourceHandles {
+            fbs: vec![handle(7)],
+            crtcs: vec![handle(40)],
+            connectors: vec![handle(30)],
+            encoders: vec![handle(35)],
+            ..Default::default()
+        };
+        device.resource_handles_into(&mut res).unwrap_err();
+        assert!(res.framebuffers().is_empty());
+        assert_eq!(res.all_handles().count(), 0);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn mode_json_round_trip() {
+        let mode = Mode::new(
+            "1920x1080",
+            148500,
+            1920,
+            2008,
+            2052,
+            2200,
+            1080,
+            1084,
+            1089,
+            1125,
+            60,
+            ModeFlags::PHSYNC | ModeFlags::PVSYNC,
+            ModeTypeFlags::DRIVER | ModeTypeFlags::PREFERRED,
+        );
+        let json = serde_json::to_string(&mode).unwrap();
+        // Timings are serialized by name, not as raw bytes
+        assert!(json.contains("\"name\":\"1920x1080\""));
+        assert!(json.contains("\"htotal\":2200"));
+        assert_eq!(serde_json::from_str::<Mode>(&json).unwrap(), mode);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn handle_json_round_trip() {
+        let crtc: crtc::Handle = handle(42);
+        assert_eq!(serde_json::to_string(&crtc).unwrap(), "42");
+        assert_eq!(serde_json::from_str::<crtc::Handle>("42").unwrap(), crtc);
+        assert!(serde_json::from_str::<crtc::Handle>("0").is_err());
+    }
+
+    /// Reports connectors `1`, `2` and `3` as connected, the others as
+    /// disconnected or in an unknown state
+    struct ConnectorStates {
+        mock: crate::mock::MockDevice,
+    }
+
+    impl AsFd for ConnectorStates {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for ConnectorStates {}
+    impl Device for ConnectorStates {
+        fn get_connector(
+            &self,
+            handle: connector::Handle,
+            _force_probe: bool,
+        ) -> io::Result<connector::Info> {
+            let connection = match u32::from(handle) {
+                1..=3 => connector::State::Connected,
+                4 | 5 => connector::State::Disconnected,
+                _ => connector::State::Unknown,
+            };
+            Ok(connector::Info {
+                handle,
+                interface: connector::Interface::DisplayPort,
+                interface_id: handle.into(),
+                connection,
+                size: None,
+                modes: Vec::new(),
+                encoders: Vec::new(),
+                curr_enc: None,
+                subpixel: connector::SubPixel::Unknown,
+            })
+        }
+    }
+
+    #[test]
+    fn connected_connectors() {
+        let device = ConnectorStates {
+            mock: crate::mock::MockDevice::new().unwrap(),
+        };
+        let res = ResourceHandles {
+            connectors: [4, 3, 6, 1, 5, 2].into_iter().map(handle).collect(),
+            ..Default::default()
+        };
+
+        let connected = res.connected_connectors(&device, false).unwrap();
+        let handles: Vec<connector::Handle> = connected.iter().map(|info| info.handle()).collect();
+        assert_eq!(handles, [handle(3), handle(1), handle(2)]);
+        assert!(connected
+            .iter()
+            .all(|info| info.state() == connector::State::Connected));
+
+        let res = ResourceHandles {
+            connectors: vec![handle(4), handle(6)],
+            ..Default::default()
+        };
+        assert!(res.connected_connectors(&device, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn all_handles_order() {
+        let res = ResourceHandles {
+            fbs: vec![handle(7), handle(3)],
+            crtcs: vec![handle(2)],
+            connectors: vec![handle(9), handle(1)],
+            encoders: vec![handle(5)],
+            ..Default::default()
+        };
+        let all: Vec<u32> = res.all_handles().map(|raw| raw.get()).collect();
+        assert_eq!(all, [7, 3, 2, 9, 1, 5]);
+
+        assert_eq!(ResourceHandles::default().all_handles().count(), 0);
+    }
+
+    #[test]
+    fn legacy_framebuffer_formats() {
+        assert_eq!(legacy_framebuffer_format(24, 32), Some(DrmFourcc::Xrgb8888));
+        assert_eq!(legacy_depth_bpp(DrmFourcc::Xrgb8888).unwrap(), (24, 32));
+        assert_eq!(legacy_depth_bpp(DrmFourcc::Argb8888).unwrap(), (32, 32));
+        assert_eq!(legacy_depth_bpp(DrmFourcc::Rgb565).unwrap(), (16, 16));
+
+        // Planar formats can not be described by depth and bpp
+        let err = legacy_depth_bpp(DrmFourcc::Nv12).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+        // Nor can formats the kernel would mistake for another one
+        let err = legacy_depth_bpp(DrmFourcc::Xbgr8888).unwrap_err();
+        assert_eq!(err.raw_os_error(), Some(Errno::INVAL.raw_os_error()));
+    }
+
+    #[test]
+    fn unknown_objects() {
+        let err = |errno: Errno| -> io::Result<()> { Err(errno.into()) };
+        assert_eq!(unknown_object_as_none(Ok(3)).unwrap(), Some(3));
+        assert_eq!(unknown_object_as_none(err(Errno::NOENT)).unwrap(), None);
+        assert_eq!(unknown_object_as_none(err(Errno::INVAL)).unwrap(), None);
+
+        let other = unknown_object_as_none(err(Errno::ACCESS)).unwrap_err();
+        assert_eq!(other.raw_os_error(), Some(Errno::ACCESS.raw_os_error()));
+    }
+
+    /// Records destroyed framebuffers, failing to destroy framebuffer `2`
+    struct FbDestroyer {
+        mock: crate::mock::MockDevice,
+        attempted: std::cell::RefCell<Vec<framebuffer::Handle>>,
+    }
+
+    impl AsFd for FbDestroyer {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for FbDestroyer {}
+    impl Device for FbDestroyer {
+        fn destroy_framebuffer(&self, handle: framebuffer::Handle) -> io::Result<()> {
+            self.attempted.borrow_mut().push(handle);
+            match u32::from(handle) {
+                2 => Err(Errno::BUSY.into()),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn destroy_framebuffers_continues_after_error() {
+        let device = FbDestroyer {
+            mock: crate::mock::MockDevice::new().unwrap(),
+            attempted: Default::default(),
+        };
+        let handles = [handle(1), handle(2), handle(3)];
+
+        let results = device.destroy_framebuffers(&handles);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert_eq!(
+            results[1].as_ref().unwrap_err().raw_os_error(),
+            Some(Errno::BUSY.raw_os_error())
+        );
+        assert!(results[2].is_ok());
+        assert_eq!(*device.attempted.borrow(), handles);
+    }
+
+    /// Completes atomic commits by queueing events on a mock device: a flip of
+    /// another crtc, a vblank, the flip of the commit and another unrelated flip
+    struct FlipQueue {
+        mock: crate::mock::MockDevice,
+    }
+
+    impl AsFd for FlipQueue {
+        fn as_fd(&self) -> BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl crate::Device for FlipQueue {}
+    impl Device for FlipQueue {
+        fn atomic_commit_with_user_data(
+            &self,
+            _flags: AtomicCommitFlags,
+            _req: atomic::AtomicModeReq,
+            user_data: u64,
+        ) -> io::Result<()> {
+            self.mock.push_page_flip_event(handle(41), 1, 0)?;
+            self.mock
+                .push_vblank_event(handle(41), 2, Duration::from_millis(16), 0)?;
+            self.mock.push_page_flip_event(handle(40), 3, user_data)?;
+            self.mock.push_page_flip_event(handle(42), 4, 0)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn atomic_commit_blocking_keeps_other_events() {
+        let device = FlipQueue {
+            mock: crate::mock::MockDevice::new().unwrap(),
+        };
+
+        let mut skipped = Vec::new();
+        let event = device
+            .atomic_commit_blocking(
+                AtomicCommitFlags::empty(),
+                atomic::AtomicModeReq::new(),
+                Some(Duration::from_secs(1)),
+                &mut skipped,
+            )
+            .unwrap_or_else(|err| panic!("commit failed: {}", err));
+        assert_eq!(event.frame, 3);
+        assert_ne!(event.user_data, 0);
+
+        assert_eq!(skipped.len(), 3);
+        assert!(matches!(&skipped[0], Event::PageFlip(event) if event.frame == 1));
+        assert!(matches!(&skipped[1], Event::Vblank(event) if event.frame == 2));
+        assert!(matches!(&skipped[2], Event::PageFlip(event) if event.frame == 4));
+    }
+
+    #[test]
+    fn atomic_commit_blocking_timeout() {
+        /// Completes commits with an unrelated event only
+        struct NoFlip(crate::mock::MockDevice);
+
+        impl AsFd for NoFlip {
+            fn as_fd(&self) -> BorrowedFd<'_> {
+                self.0.as_fd()
+            }
+        }
+
+        impl crate::Device for NoFlip {}
+        impl Device for NoFlip {
+            fn atomic_commit_with_user_data(
+                &self,
+                _flags: AtomicCommitFlags,
+                _req: atomic::AtomicModeReq,
+                _user_data: u64,
+            ) -> io::Result<()> {
+                self.0.push_page_flip_event(handle(41), 1, 0)
+            }
+        }
+
+        let device = NoFlip(crate::mock::MockDevice::new().unwrap());
+        let mut skipped = Vec::new();
+        let err = device
+            .atomic_commit_blocking(
+                AtomicCommitFlags::empty(),
+                atomic::AtomicModeReq::new(),
+                Some(Duration::from_millis(10)),
+                &mut skipped,
+            )
+            .err()
+            .unwrap();
+        assert_eq!(err.raw_os_error(), Some(Errno::TIMEDOUT.raw_os_error()));
+        assert_eq!(skipped.len(), 1);
+    }
+}
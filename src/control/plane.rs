@@ -7,13 +7,13 @@
 //! types of planes available for use:
 //!
 //! * Primary - A CRTC's built-in plane. When attaching a framebuffer to a CRTC,
-//! it is actually being attached to this kind of plane.
+//!   it is actually being attached to this kind of plane.
 //!
 //! * Overlay - Can be overlaid on top of a primary plane, utilizing extremely
-//! fast hardware compositing.
+//!   fast hardware compositing.
 //!
 //! * Cursor - Similar to an overlay plane, these are typically used to display
-//! cursor type objects.
+//!   cursor type objects.
 
 use crate::control;
 use drm_ffi as ffi;
@@ -21,6 +21,11 @@ use drm_ffi as ffi;
 /// A handle to a plane
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(control::RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -55,8 +60,22 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "plane::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about a plane
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Info {
     pub(crate) handle: Handle,
     pub(crate) crtc: Option<control::crtc::Handle>,
@@ -90,6 +109,14 @@ impl Info {
         control::CrtcListFilter(self.pos_crtcs)
     }
 
+    /// Returns the crtcs of `resources` this plane supports.
+    pub fn possible_crtc_handles(
+        &self,
+        resources: &control::ResourceHandles,
+    ) -> Vec<control::crtc::Handle> {
+        resources.filter_crtcs(self.possible_crtcs())
+    }
+
     /// Returns the framebuffer this plane is attached to.
     pub fn framebuffer(&self) -> Option<control::framebuffer::Handle> {
         self.fb
@@ -100,3 +127,68 @@ impl Info {
         &self.formats
     }
 }
+
+/// How the pixels of a plane are blended with the planes below it, as
+/// controlled by the `pixel blend mode` plane property.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub enum BlendMode {
+    /// The alpha channel of the framebuffer is ignored
+    None,
+    /// The color channels of the framebuffer are premultiplied with its alpha channel
+    PreMultiplied,
+    /// The color channels of the framebuffer are not premultiplied
+    Coverage,
+}
+
+impl BlendMode {
+    /// Get the name the kernel uses for this value of the `pixel blend mode` property
+    pub fn as_str(&self) -> &'static str {
+        // source: drm_blend.c, drm_plane_create_blend_mode_property
+        match self {
+            BlendMode::None => "None",
+            BlendMode::PreMultiplied => "Pre-multiplied",
+            BlendMode::Coverage => "Coverage",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "plane::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    #[test]
+    fn possible_crtc_handles() {
+        let crtc = |id| control::from_u32::<control::crtc::Handle>(id).unwrap();
+        let resources = control::ResourceHandles {
+            crtcs: vec![crtc(40), crtc(41), crtc(42), crtc(43)],
+            ..Default::default()
+        };
+        let info = |pos_crtcs| Info {
+            handle: control::from_u32(30).unwrap(),
+            crtc: None,
+            fb: None,
+            pos_crtcs,
+            formats: Vec::new(),
+        };
+
+        // Bits refer to the index of the crtc within the resources
+        assert_eq!(
+            info(0b1010).possible_crtc_handles(&resources),
+            [crtc(41), crtc(43)]
+        );
+        assert_eq!(
+            info(0b1111).possible_crtc_handles(&resources),
+            resources.crtcs
+        );
+        assert!(info(0).possible_crtc_handles(&resources).is_empty());
+        // Bits beyond the known crtcs are ignored
+        assert_eq!(info(0b1_0001).possible_crtc_handles(&resources), [crtc(40)]);
+    }
+}
@@ -20,6 +20,11 @@ pub type RawValue = u64;
 /// A handle to a property
 #[repr(transparent)]
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
 pub struct Handle(RawResourceHandle);
 
 // Safety: Handle is repr(transparent) over NonZeroU32
@@ -54,6 +59,19 @@ impl std::fmt::Debug for Handle {
     }
 }
 
+impl std::fmt::Display for Handle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "property::Handle({})", self.0)
+    }
+}
+
+impl Handle {
+    /// Returns the raw id of this handle.
+    pub fn as_raw(&self) -> u32 {
+        self.0.get()
+    }
+}
+
 /// Information about a property
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Info {
@@ -85,7 +103,28 @@ impl Info {
         self.mutable
     }
 
-    /// Returns whether this property can be atomically updated.
+    /// Returns the name of the enum value `raw`, or [`None`] if this is not an
+    /// enum property or `raw` is not one of its values.
+    pub fn enum_value_name(&self, raw: RawValue) -> Option<&std::ffi::CStr> {
+        match &self.val_type {
+            ValueType::Enum(values) => values.get_value_from_raw_value(raw).map(EnumValue::name),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw value of the enum value called `name`, or [`None`] if
+    /// this is not an enum property or has no such value.
+    pub fn enum_value_by_name(&self, name: &str) -> Option<RawValue> {
+        match &self.val_type {
+            ValueType::Enum(values) => values.get_value_from_name(name).map(EnumValue::value),
+            _ => None,
+        }
+    }
+
+    /// Returns whether this property is only exposed to clients that enabled
+    /// the [`Atomic`](crate::ClientCapability::Atomic) client capability.
+    ///
+    /// Properties without this flag can be used in atomic commits as well.
     pub fn atomic(&self) -> bool {
         self.atomic
     }
@@ -128,7 +167,7 @@ pub enum ValueType {
 
 impl ValueType {
     /// Given a [`RawValue`], convert it into a specific [`Value`]
-    pub fn convert_value(&self, value: RawValue) -> Value {
+    pub fn convert_value(&self, value: RawValue) -> Value<'_> {
         match self {
             ValueType::Unknown => Value::Unknown(value),
             ValueType::Boolean => Value::Boolean(value != 0),
@@ -216,6 +255,28 @@ macro_rules! match_variant {
 }
 
 impl<'a> Value<'a> {
+    /// Converts a [`RawValue`] referring to an object of a known type into the
+    /// matching variant.
+    ///
+    /// Blobs become [`Value::Blob`], object types without a variant of their
+    /// own become [`Value::Object`].
+    pub fn from_object(obj_type: super::ObjectType, value: RawValue) -> Value<'static> {
+        use super::ObjectType;
+
+        match obj_type {
+            ObjectType::CRTC => Value::CRTC(bytemuck::cast(value as u32)),
+            ObjectType::Connector => Value::Connector(bytemuck::cast(value as u32)),
+            ObjectType::Encoder => Value::Encoder(bytemuck::cast(value as u32)),
+            ObjectType::Framebuffer => Value::Framebuffer(bytemuck::cast(value as u32)),
+            ObjectType::Plane => Value::Plane(bytemuck::cast(value as u32)),
+            ObjectType::Property => Value::Property(bytemuck::cast(value as u32)),
+            ObjectType::Blob => Value::Blob(value),
+            ObjectType::Any | ObjectType::Mode | ObjectType::Unknown(_) => {
+                Value::Object(bytemuck::cast(value as u32))
+            }
+        }
+    }
+
     /// Boolean value
     pub fn as_boolean(&self) -> Option<bool> {
         match_variant!(self, Boolean)
@@ -339,4 +400,143 @@ impl EnumValues {
         };
         Some(&enums[index])
     }
+
+    /// Returns the [`EnumValue`] with the given name, or [`None`] if no value
+    /// of this [`EnumValues`] carries that name.
+    pub fn get_value_from_name(&self, name: &str) -> Option<&EnumValue> {
+        self.enums
+            .iter()
+            .find(|value| value.name().to_bytes() == name.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_display() {
+        let handle: Handle = crate::control::from_u32(42).unwrap();
+        assert_eq!(handle.to_string(), "property::Handle(42)");
+        assert_eq!(handle.as_raw(), 42);
+    }
+
+    fn enum_value(value: RawValue, name: &str) -> EnumValue {
+        let mut inner = ffi::drm_mode_property_enum {
+            value,
+            ..Default::default()
+        };
+        for (dst, &src) in inner.name.iter_mut().zip(name.as_bytes()) {
+            *dst = src as _;
+        }
+        EnumValue(inner)
+    }
+
+    fn info(val_type: ValueType) -> Info {
+        Info {
+            handle: Handle::from(RawResourceHandle::new(1).unwrap()),
+            val_type,
+            mutable: true,
+            atomic: false,
+            info: Default::default(),
+        }
+    }
+
+    fn scaling_mode() -> Info {
+        info(ValueType::Enum(EnumValues {
+            values: vec![0, 1, 3],
+            enums: vec![
+                enum_value(0, "None"),
+                enum_value(1, "Full"),
+                enum_value(3, "Full aspect"),
+            ],
+        }))
+    }
+
+    #[test]
+    fn enum_value_name() {
+        let info = scaling_mode();
+        assert_eq!(info.enum_value_name(0).unwrap().to_bytes(), b"None");
+        assert_eq!(info.enum_value_name(3).unwrap().to_bytes(), b"Full aspect");
+        assert_eq!(info.enum_value_name(2), None);
+    }
+
+    #[test]
+    fn enum_value_by_name() {
+        let info = scaling_mode();
+        assert_eq!(info.enum_value_by_name("Full"), Some(1));
+        assert_eq!(info.enum_value_by_name("Full aspect"), Some(3));
+        assert_eq!(info.enum_value_by_name("Center"), None);
+    }
+
+    #[test]
+    fn non_enum_lookups() {
+        let info = info(ValueType::UnsignedRange(0, 3));
+        assert_eq!(info.enum_value_name(0), None);
+        assert_eq!(info.enum_value_by_name("None"), None);
+    }
+
+    #[test]
+    fn value_from_object() {
+        use crate::control::{from_u32, ObjectType};
+
+        assert_eq!(
+            Value::from_object(ObjectType::CRTC, 40),
+            Value::CRTC(from_u32(40))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Connector, 30),
+            Value::Connector(from_u32(30))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Encoder, 35),
+            Value::Encoder(from_u32(35))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Framebuffer, 90),
+            Value::Framebuffer(from_u32(90))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Plane, 3),
+            Value::Plane(from_u32(3))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Property, 5),
+            Value::Property(from_u32(5))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Blob, 1 << 40),
+            Value::Blob(1 << 40)
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Mode, 7),
+            Value::Object(RawResourceHandle::new(7))
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Unknown(0x1234), 8),
+            Value::Object(RawResourceHandle::new(8))
+        );
+
+        // A raw value of 0 refers to no object
+        assert_eq!(Value::from_object(ObjectType::CRTC, 0), Value::CRTC(None));
+        assert_eq!(
+            Value::from_object(ObjectType::Connector, 0),
+            Value::Connector(None)
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Encoder, 0),
+            Value::Encoder(None)
+        );
+        assert_eq!(
+            Value::from_object(ObjectType::Framebuffer, 0),
+            Value::Framebuffer(None)
+        );
+        assert_eq!(Value::from_object(ObjectType::Plane, 0), Value::Plane(None));
+        assert_eq!(
+            Value::from_object(ObjectType::Property, 0),
+            Value::Property(None)
+        );
+        assert_eq!(Value::from_object(ObjectType::Any, 0), Value::Object(None));
+        assert_eq!(Value::from_object(ObjectType::Blob, 0), Value::Blob(0));
+    }
 }
@@ -0,0 +1,185 @@
+//! # Tile
+//!
+//! Layout information of tiled displays and DisplayPort MST connectors.
+//!
+//! Some displays are too large to be driven by a single connector and are
+//! instead exposed as multiple connectors, each driving a tile of the same
+//! logical display. Connectors of such displays carry a `TILE` property,
+//! describing where the tile is placed within the display.
+//!
+//! Connectors created for DisplayPort Multi-Stream Transport carry a `PATH`
+//! property, describing how they are reached from their parent connector.
+
+use crate::control;
+
+/// Placement of a connector's tile within a tiled display, parsed from the
+/// connector's `TILE` property blob.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct TileInfo {
+    pub(crate) group_id: u32,
+    pub(crate) single_monitor: bool,
+    pub(crate) num_tiles: (u32, u32),
+    pub(crate) location: (u32, u32),
+    pub(crate) tile_size: (u32, u32),
+}
+
+impl TileInfo {
+    /// Parses the contents of a `TILE` property blob.
+    ///
+    /// Returns [`None`] if the blob is not formatted as expected.
+    pub fn parse(blob: &[u8]) -> Option<TileInfo> {
+        let text = blob_str(blob)?;
+        let mut fields = [0u32; 8];
+        let mut parts = text.split(':');
+        for field in fields.iter_mut() {
+            *field = parts.next()?.trim().parse().ok()?;
+        }
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(TileInfo {
+            group_id: fields[0],
+            single_monitor: fields[1] != 0,
+            num_tiles: (fields[2], fields[3]),
+            location: (fields[4], fields[5]),
+            tile_size: (fields[6], fields[7]),
+        })
+    }
+
+    /// Returns the id of the tile group. All connectors driving the same
+    /// display share the same group id.
+    pub fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// Returns whether all tiles belong to a single physical monitor.
+    pub fn single_monitor(&self) -> bool {
+        self.single_monitor
+    }
+
+    /// Returns the total number of horizontal and vertical tiles.
+    pub fn num_tiles(&self) -> (u32, u32) {
+        self.num_tiles
+    }
+
+    /// Returns the horizontal and vertical location of this tile, counted in tiles.
+    pub fn location(&self) -> (u32, u32) {
+        self.location
+    }
+
+    /// Returns the size of this tile in pixels.
+    pub fn tile_size(&self) -> (u32, u32) {
+        self.tile_size
+    }
+}
+
+/// How a connector is reached, parsed from the connector's `PATH` property blob.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum PathInfo {
+    /// Connector created for a DisplayPort MST port.
+    Mst {
+        /// The connector the MST topology is attached to
+        parent: Option<control::connector::Handle>,
+        /// Port numbers leading from the parent connector to this one, separated by `-`
+        path: String,
+    },
+    /// A path not recognized by drm-rs
+    Unknown(String),
+}
+
+impl PathInfo {
+    /// Parses the contents of a `PATH` property blob.
+    ///
+    /// Returns [`None`] if the blob is not a valid string.
+    pub fn parse(blob: &[u8]) -> Option<PathInfo> {
+        let text = blob_str(blob)?;
+
+        if let Some(mst) = text.strip_prefix("mst:") {
+            if let Some((parent, path)) = mst.split_once('-') {
+                if let Ok(parent) = parent.parse::<u32>() {
+                    return Some(PathInfo::Mst {
+                        parent: control::from_u32(parent),
+                        path: path.to_owned(),
+                    });
+                }
+            }
+        }
+
+        Some(PathInfo::Unknown(text.to_owned()))
+    }
+}
+
+/// Blobs holding strings are NUL terminated.
+fn blob_str(blob: &[u8]) -> Option<&str> {
+    let end = blob.iter().position(|&b| b == 0).unwrap_or(blob.len());
+    std::str::from_utf8(&blob[..end]).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PathInfo, TileInfo};
+    use crate::control::from_u32;
+
+    #[test]
+    fn parse_tile() {
+        let tile = TileInfo::parse(b"2:1:2:1:1:0:1920:2160\0").unwrap();
+        assert_eq!(tile.group_id(), 2);
+        assert!(tile.single_monitor());
+        assert_eq!(tile.num_tiles(), (2, 1));
+        assert_eq!(tile.location(), (1, 0));
+        assert_eq!(tile.tile_size(), (1920, 2160));
+
+        // The terminating NUL is optional
+        assert_eq!(TileInfo::parse(b"2:1:2:1:1:0:1920:2160"), Some(tile));
+    }
+
+    #[test]
+    fn parse_tile_wrong_field_count() {
+        assert_eq!(TileInfo::parse(b"2:1:2:1:1:0:1920\0"), None);
+        assert_eq!(TileInfo::parse(b"2:1:2:1:1:0:1920:2160:0\0"), None);
+        assert_eq!(TileInfo::parse(b"\0"), None);
+    }
+
+    #[test]
+    fn parse_tile_non_numeric() {
+        assert_eq!(TileInfo::parse(b"2:1:2:1:x:0:1920:2160\0"), None);
+        assert_eq!(TileInfo::parse(b"2:1:2:1:-1:0:1920:2160\0"), None);
+        assert_eq!(TileInfo::parse(b"2:1:2:1:1:0:1920:\xff\0"), None);
+    }
+
+    #[test]
+    fn parse_path() {
+        assert_eq!(
+            PathInfo::parse(b"mst:55-2-1\0"),
+            Some(PathInfo::Mst {
+                parent: from_u32(55),
+                path: "2-1".to_owned(),
+            })
+        );
+        assert_eq!(
+            PathInfo::parse(b"mst:0-1\0"),
+            Some(PathInfo::Mst {
+                parent: None,
+                path: "1".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn parse_path_unknown() {
+        assert_eq!(
+            PathInfo::parse(b"mst:abc-1\0"),
+            Some(PathInfo::Unknown("mst:abc-1".to_owned()))
+        );
+        assert_eq!(
+            PathInfo::parse(b"mst:55\0"),
+            Some(PathInfo::Unknown("mst:55".to_owned()))
+        );
+        assert_eq!(
+            PathInfo::parse(b"other\0"),
+            Some(PathInfo::Unknown("other".to_owned()))
+        );
+        assert_eq!(PathInfo::parse(b"\xff\0"), None);
+    }
+}
@@ -32,9 +32,14 @@ pub(crate) mod util;
 
 pub mod buffer;
 pub mod control;
+#[cfg(any(test, feature = "test-mock"))]
+pub mod mock;
 pub mod node;
 
+use std::borrow::Cow;
+use std::error;
 use std::ffi::{OsStr, OsString};
+use std::fmt;
 use std::time::Duration;
 use std::{
     io,
@@ -116,9 +121,30 @@ pub trait Device: AsFd {
         Ok(())
     }
 
+    /// Checks whether this file descriptor currently holds the DRM Master lock.
+    fn is_master(&self) -> io::Result<bool> {
+        // Authenticating requires DRM Master, while 0 is never a valid token.
+        // Masters therefore fail with EINVAL, everyone else with EACCES.
+        match drm_ffi::auth::auth_magic_token(self.as_fd(), 0) {
+            Err(err) if err.raw_os_error() == Some(Errno::ACCESS.raw_os_error()) => Ok(false),
+            Err(err) if err.raw_os_error() == Some(Errno::INVAL.raw_os_error()) => Ok(true),
+            Err(err) => Err(err),
+            Ok(_) => Ok(true),
+        }
+    }
+
     /// Generates an [`AuthToken`] for this process.
     #[deprecated(note = "Consider opening a render node instead.")]
     fn generate_auth_token(&self) -> io::Result<AuthToken> {
+        self.magic_token()
+    }
+
+    /// Gets the [`AuthToken`] of this file descriptor, to be authenticated by
+    /// the process holding the DRM Master lock.
+    ///
+    /// See [`AuthToken`] for a description of the authentication flow. New code
+    /// should prefer opening a render node, which requires no authentication.
+    fn magic_token(&self) -> io::Result<AuthToken> {
         let token = drm_ffi::auth::get_magic_token(self.as_fd())?;
         Ok(AuthToken(token.magic))
     }
@@ -131,11 +157,32 @@ pub trait Device: AsFd {
 
     /// Requests the driver to expose or hide certain capabilities. See
     /// [`ClientCapability`] for more information.
+    ///
+    /// # Notes
+    ///
+    /// The kernel provides no way to query the current state of a client
+    /// capability, so callers need to keep track of the capabilities they enabled.
     fn set_client_capability(&self, cap: ClientCapability, enable: bool) -> io::Result<()> {
         drm_ffi::set_capability(self.as_fd(), cap as u64, enable)?;
         Ok(())
     }
 
+    /// Requests the driver to expose or hide several capabilities at once.
+    ///
+    /// Capabilities are applied in order. Processing stops at the first
+    /// capability the driver rejects, which is reported in the returned error.
+    /// Capabilities preceding it remain applied.
+    fn set_client_capabilities(
+        &self,
+        caps: &[(ClientCapability, bool)],
+    ) -> Result<(), ClientCapabilityError> {
+        for &(capability, enable) in caps {
+            self.set_client_capability(capability, enable)
+                .map_err(|source| ClientCapabilityError { capability, source })?;
+        }
+        Ok(())
+    }
+
     /// Gets the bus ID of this device.
     fn get_bus_id(&self) -> io::Result<OsString> {
         let mut buffer = Vec::new();
@@ -145,6 +192,11 @@ pub trait Device: AsFd {
         Ok(bus_id)
     }
 
+    /// Gets the bus ID of this device, parsed into a [`BusId`].
+    fn bus_id_parsed(&self) -> io::Result<BusId> {
+        Ok(BusId::from(self.get_bus_id()?.as_os_str()))
+    }
+
     /// Check to see if our [`AuthToken`] has been authenticated
     /// by the DRM Master
     fn authenticated(&self) -> io::Result<bool> {
@@ -158,6 +210,14 @@ pub trait Device: AsFd {
         Ok(cap.value)
     }
 
+    /// Returns whether a capability is supported, i.e. has a non-zero value.
+    ///
+    /// Use [`Device::get_driver_capability`] for capabilities carrying a value,
+    /// such as [`DriverCapability::CursorWidth`].
+    fn has_capability(&self, cap: DriverCapability) -> io::Result<bool> {
+        Ok(self.get_driver_capability(cap)? != 0)
+    }
+
     /// # Possible errors:
     ///   - `EFAULT`: Kernel could not copy fields into userspace
     #[allow(missing_docs)]
@@ -188,7 +248,46 @@ pub trait Device: AsFd {
         Ok(driver)
     }
 
+    /// Returns the driver version along with the values of all
+    /// [`DriverCapability`]s, e.g. for diagnostics.
+    ///
+    /// Capabilities the driver fails to report are recorded as [`None`]
+    /// instead of failing the whole call.
+    fn describe(&self) -> io::Result<DeviceDescription> {
+        let driver = self.get_driver()?;
+        let capabilities = DriverCapability::ALL
+            .iter()
+            .map(|&cap| (cap, self.get_driver_capability(cap).ok()))
+            .collect();
+
+        Ok(DeviceDescription {
+            driver,
+            capabilities,
+        })
+    }
+
+    /// Returns the cursor size supported by the driver, as `(width, height)`.
+    ///
+    /// Cursor buffers should be allocated with exactly this size. Drivers not
+    /// reporting a size fall back to the kernel default of 64x64.
+    fn cursor_size(&self) -> io::Result<(u64, u64)> {
+        Ok((
+            self.get_driver_capability(DriverCapability::CursorWidth)?,
+            self.get_driver_capability(DriverCapability::CursorHeight)?,
+        ))
+    }
+
+    /// Returns whether PRIME buffers can be imported and exported, in that order.
+    fn prime_import_export(&self) -> io::Result<(bool, bool)> {
+        let prime = self.get_driver_capability(DriverCapability::Prime)?;
+        Ok(decode_prime_capability(prime))
+    }
+
     /// Waits for a vblank.
+    ///
+    /// `high_crtc` is the index of the crtc in the device's list of crtcs, not
+    /// its handle. [`control::Device::wait_vblank_for_crtc`] computes it from a
+    /// crtc handle instead.
     fn wait_vblank(
         &self,
         target_sequence: VblankWaitTarget,
@@ -196,36 +295,63 @@ pub trait Device: AsFd {
         high_crtc: u32,
         user_data: usize,
     ) -> io::Result<VblankWaitReply> {
-        use drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_HIGH_CRTC_MASK;
-        use drm_ffi::_DRM_VBLANK_HIGH_CRTC_SHIFT;
+        let (type_, sequence) = vblank_request(target_sequence, flags, high_crtc)?;
+        let reply = drm_ffi::wait_vblank(self.as_fd(), type_, sequence, user_data)?;
 
-        let high_crtc_mask = _DRM_VBLANK_HIGH_CRTC_MASK >> _DRM_VBLANK_HIGH_CRTC_SHIFT;
-        if (high_crtc & !high_crtc_mask) != 0 {
-            return Err(Errno::INVAL.into());
-        }
+        Ok(VblankWaitReply::from(reply))
+    }
 
-        let (sequence, wait_type) = match target_sequence {
-            VblankWaitTarget::Absolute(n) => {
-                (n, drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_ABSOLUTE)
-            }
-            VblankWaitTarget::Relative(n) => {
-                (n, drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_RELATIVE)
-            }
-        };
+    /// Requests a vblank event, returning the absolute sequence the event
+    /// will carry.
+    ///
+    /// This is [`Device::wait_vblank`] with [`VblankWaitFlags::EVENT`] set,
+    /// which returns right away instead of blocking. The event is delivered
+    /// as [`control::Event::Vblank`] once the target vblank is reached, unless
+    /// [`VblankWaitFlags::NEXT_ON_MISS`] moved it to a later vblank, in which
+    /// case the event carries that later sequence.
+    fn request_vblank_event(
+        &self,
+        target_sequence: VblankWaitTarget,
+        flags: VblankWaitFlags,
+        high_crtc: u32,
+        user_data: usize,
+    ) -> io::Result<u32> {
+        let flags = flags | VblankWaitFlags::EVENT;
+        let reply = self.wait_vblank(target_sequence, flags, high_crtc, user_data)?;
+        Ok(reply.sequence())
+    }
+}
 
-        let type_ = wait_type | (high_crtc << _DRM_VBLANK_HIGH_CRTC_SHIFT) | flags.bits();
-        let reply = drm_ffi::wait_vblank(self.as_fd(), type_, sequence, user_data)?;
+/// Encodes the type and sequence of a vblank wait request, placing the index
+/// of the crtc into the high crtc bits of the type.
+fn vblank_request(
+    target_sequence: VblankWaitTarget,
+    flags: VblankWaitFlags,
+    high_crtc: u32,
+) -> io::Result<(u32, u32)> {
+    use drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_HIGH_CRTC_MASK;
+    use drm_ffi::_DRM_VBLANK_HIGH_CRTC_SHIFT;
+
+    let high_crtc_mask = _DRM_VBLANK_HIGH_CRTC_MASK >> _DRM_VBLANK_HIGH_CRTC_SHIFT;
+    if (high_crtc & !high_crtc_mask) != 0 {
+        return Err(Errno::INVAL.into());
+    }
 
-        let time = match (reply.tval_sec, reply.tval_usec) {
-            (0, 0) => None,
-            (sec, usec) => Some(Duration::new(sec as u64, (usec * 1000) as u32)),
-        };
+    let (sequence, wait_type) = match target_sequence {
+        VblankWaitTarget::Absolute(n) => (n, drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_ABSOLUTE),
+        VblankWaitTarget::Relative(n) => (n, drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_RELATIVE),
+    };
 
-        Ok(VblankWaitReply {
-            frame: reply.sequence,
-            time,
-        })
-    }
+    let type_ = wait_type | (high_crtc << _DRM_VBLANK_HIGH_CRTC_SHIFT) | flags.bits();
+    Ok((type_, sequence))
+}
+
+/// Splits the value of [`DriverCapability::Prime`] into import and export support.
+fn decode_prime_capability(value: u64) -> (bool, bool) {
+    (
+        value & drm_ffi::DRM_PRIME_CAP_IMPORT as u64 != 0,
+        value & drm_ffi::DRM_PRIME_CAP_EXPORT as u64 != 0,
+    )
 }
 
 /// An authentication token, unique to the file descriptor of the device.
@@ -233,6 +359,17 @@ pub trait Device: AsFd {
 /// This token can be sent to another process that owns the DRM Master lock to
 /// allow unprivileged use of the device, such as rendering.
 ///
+/// # Authentication Flow
+///
+/// 1. The unprivileged process opens the primary node and obtains its token
+///    using [`Device::magic_token`].
+/// 2. The token is passed to the DRM Master as a plain `u32`, e.g. over a
+///    socket. It can be converted using [`From`] in both directions.
+/// 3. The DRM Master calls [`Device::authenticate_auth_token`] on its own file
+///    descriptor.
+/// 4. The unprivileged process can confirm the result using
+///    [`Device::authenticated`].
+///
 /// # Deprecation Notes
 ///
 /// This method of authentication is somewhat deprecated. Accessing unprivileged
@@ -242,6 +379,18 @@ pub trait Device: AsFd {
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub struct AuthToken(u32);
 
+impl From<AuthToken> for u32 {
+    fn from(token: AuthToken) -> u32 {
+        token.0
+    }
+}
+
+impl From<u32> for AuthToken {
+    fn from(magic: u32) -> AuthToken {
+        AuthToken(magic)
+    }
+}
+
 /// Driver version of a device.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Driver {
@@ -270,6 +419,83 @@ impl Driver {
     pub fn description(&self) -> &OsStr {
         self.desc.as_ref()
     }
+
+    /// Name of driver, with invalid UTF-8 replaced
+    pub fn name_str(&self) -> Cow<'_, str> {
+        self.name.to_string_lossy()
+    }
+
+    /// Date driver was published, with invalid UTF-8 replaced
+    pub fn date_str(&self) -> Cow<'_, str> {
+        self.date.to_string_lossy()
+    }
+
+    /// Returns whether the driver version is at least `major.minor.patch`.
+    pub fn version_at_least(&self, major: i32, minor: i32, patch: i32) -> bool {
+        self.version >= (major, minor, patch)
+    }
+}
+
+impl fmt::Display for Driver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (major, minor, patch) = self.version;
+        write!(f, "{} {}.{}.{}", self.name_str(), major, minor, patch)
+    }
+}
+
+/// Bus ID of a device, as returned by [`Device::bus_id_parsed`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum BusId {
+    /// A PCI device, such as `pci:0000:03:00.0`
+    Pci {
+        /// PCI domain
+        domain: u32,
+        /// PCI bus number
+        bus: u8,
+        /// PCI device number
+        dev: u8,
+        /// PCI function number
+        func: u8,
+    },
+    /// A platform device, such as `platform:vc4-drm`
+    Platform(String),
+    /// A USB device, such as `usb:...`
+    Usb(String),
+    /// A bus ID that could not be parsed
+    Unknown(OsString),
+}
+
+impl BusId {
+    fn parse_pci(id: &str) -> Option<BusId> {
+        let (domain, rest) = id.split_once(':')?;
+        let (bus, rest) = rest.split_once(':')?;
+        let (dev, func) = rest.split_once('.')?;
+        Some(BusId::Pci {
+            domain: u32::from_str_radix(domain, 16).ok()?,
+            bus: u8::from_str_radix(bus, 16).ok()?,
+            dev: u8::from_str_radix(dev, 16).ok()?,
+            func: func.parse().ok()?,
+        })
+    }
+}
+
+impl From<&OsStr> for BusId {
+    /// Parses a bus ID as reported by the kernel.
+    ///
+    /// PCI bus IDs are accepted with and without the `pci:` prefix, as most
+    /// drivers report their bare PCI slot name.
+    fn from(id: &OsStr) -> BusId {
+        let parsed = id.to_str().and_then(|id| {
+            if let Some(name) = id.strip_prefix("platform:") {
+                Some(BusId::Platform(name.to_owned()))
+            } else if let Some(name) = id.strip_prefix("usb:") {
+                Some(BusId::Usb(name.to_owned()))
+            } else {
+                BusId::parse_pci(id.strip_prefix("pci:").unwrap_or(id))
+            }
+        });
+        parsed.unwrap_or_else(|| BusId::Unknown(id.to_owned()))
+    }
 }
 
 /// Used to check which capabilities your graphics driver has.
@@ -279,15 +505,18 @@ impl Driver {
 pub enum DriverCapability {
     /// DumbBuffer support for scanout
     DumbBuffer = drm_ffi::DRM_CAP_DUMB_BUFFER as u64,
-    /// Unknown
+    /// Vblank waits can target CRTCs beyond the second one
     VBlankHighCRTC = drm_ffi::DRM_CAP_VBLANK_HIGH_CRTC as u64,
     /// Preferred depth to use for dumb buffers
     DumbPreferredDepth = drm_ffi::DRM_CAP_DUMB_PREFERRED_DEPTH as u64,
-    /// Unknown
+    /// Dumb buffers should be rendered to a shadow buffer and copied
     DumbPreferShadow = drm_ffi::DRM_CAP_DUMB_PREFER_SHADOW as u64,
     /// PRIME handles are supported
+    ///
+    /// The value is a bitmask of [`drm_ffi::DRM_PRIME_CAP_IMPORT`] and
+    /// [`drm_ffi::DRM_PRIME_CAP_EXPORT`], see [`Device::prime_import_export`].
     Prime = drm_ffi::DRM_CAP_PRIME as u64,
-    /// Unknown
+    /// Vblank and page flip timestamps use `CLOCK_MONOTONIC`
     MonotonicTimestamp = drm_ffi::DRM_CAP_TIMESTAMP_MONOTONIC as u64,
     /// Asynchronous page flipping support
     ASyncPageFlip = drm_ffi::DRM_CAP_ASYNC_PAGE_FLIP as u64,
@@ -299,7 +528,7 @@ pub enum DriverCapability {
     CursorHeight = drm_ffi::DRM_CAP_CURSOR_HEIGHT as u64,
     /// Create framebuffers with modifiers
     AddFB2Modifiers = drm_ffi::DRM_CAP_ADDFB2_MODIFIERS as u64,
-    /// Unknown
+    /// Page flips can target a specific vblank sequence
     PageFlipTarget = drm_ffi::DRM_CAP_PAGE_FLIP_TARGET as u64,
     /// Uses the CRTC's ID in vblank events
     CRTCInVBlankEvent = drm_ffi::DRM_CAP_CRTC_IN_VBLANK_EVENT as u64,
@@ -309,6 +538,48 @@ pub enum DriverCapability {
     TimelineSyncObj = drm_ffi::DRM_CAP_SYNCOBJ_TIMELINE as u64,
 }
 
+impl DriverCapability {
+    /// All driver capabilities known to drm-rs
+    pub const ALL: &'static [DriverCapability] = &[
+        DriverCapability::DumbBuffer,
+        DriverCapability::VBlankHighCRTC,
+        DriverCapability::DumbPreferredDepth,
+        DriverCapability::DumbPreferShadow,
+        DriverCapability::Prime,
+        DriverCapability::MonotonicTimestamp,
+        DriverCapability::ASyncPageFlip,
+        DriverCapability::AtomicASyncPageFlip,
+        DriverCapability::CursorWidth,
+        DriverCapability::CursorHeight,
+        DriverCapability::AddFB2Modifiers,
+        DriverCapability::PageFlipTarget,
+        DriverCapability::CRTCInVBlankEvent,
+        DriverCapability::SyncObj,
+        DriverCapability::TimelineSyncObj,
+    ];
+}
+
+/// Summary of a device, as returned by [`Device::describe`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DeviceDescription {
+    /// Driver version of the device
+    pub driver: Driver,
+    /// Value of each capability in [`DriverCapability::ALL`], or [`None`] if
+    /// the driver failed to report it
+    pub capabilities: Vec<(DriverCapability, Option<u64>)>,
+}
+
+impl DeviceDescription {
+    /// Returns the value of the given capability, or [`None`] if the driver
+    /// failed to report it.
+    pub fn capability(&self, cap: DriverCapability) -> Option<u64> {
+        self.capabilities
+            .iter()
+            .find(|(c, _)| *c == cap)
+            .and_then(|(_, value)| *value)
+    }
+}
+
 /// Used to enable/disable capabilities for the process.
 #[repr(u64)]
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -339,6 +610,33 @@ pub enum ClientCapability {
     CursorPlaneHotspot = drm_ffi::DRM_CLIENT_CAP_CURSOR_PLANE_HOTSPOT as u64,
 }
 
+/// Error from [`Device::set_client_capabilities`]
+#[derive(Debug)]
+pub struct ClientCapabilityError {
+    /// The capability that could not be set
+    pub capability: ClientCapability,
+    /// The error returned by the driver
+    pub source: io::Error,
+}
+
+impl fmt::Display for ClientCapabilityError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to set {:?}: {}", self.capability, self.source)
+    }
+}
+
+impl error::Error for ClientCapabilityError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<ClientCapabilityError> for io::Error {
+    fn from(err: ClientCapabilityError) -> Self {
+        err.source
+    }
+}
+
 /// Used to specify a vblank sequence to wait for
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 pub enum VblankWaitTarget {
@@ -366,15 +664,230 @@ pub struct VblankWaitReply {
     time: Option<Duration>,
 }
 
+impl From<drm_ffi::drm_wait_vblank_reply> for VblankWaitReply {
+    fn from(reply: drm_ffi::drm_wait_vblank_reply) -> Self {
+        // The kernel leaves the timestamp empty for event requests
+        let time = match (reply.tval_sec, reply.tval_usec) {
+            (0, 0) => None,
+            (sec, usec) => Some(Duration::new(sec as u64, (usec * 1000) as u32)),
+        };
+
+        VblankWaitReply {
+            frame: reply.sequence,
+            time,
+        }
+    }
+}
+
 impl VblankWaitReply {
     /// Sequence of the frame
+    ///
+    /// This is always an absolute sequence number. For
+    /// [`VblankWaitTarget::Relative`] waits, it is the sequence the relative
+    /// target was resolved to. For waits using [`VblankWaitFlags::EVENT`], it is
+    /// the sequence the vblank event will carry once delivered.
     pub fn frame(&self) -> u32 {
         self.frame
     }
 
+    /// Absolute sequence of the frame, same as [`VblankWaitReply::frame`]
+    pub fn sequence(&self) -> u32 {
+        self.frame
+    }
+
     /// Time at which the vblank occurred. [`None`] if an asynchronous event was
     /// requested
     pub fn time(&self) -> Option<Duration> {
         self.time
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prime_capability_bits() {
+        assert_eq!(decode_prime_capability(0), (false, false));
+        assert_eq!(decode_prime_capability(0x1), (true, false));
+        assert_eq!(decode_prime_capability(0x2), (false, true));
+        assert_eq!(decode_prime_capability(0x3), (true, true));
+        // Unknown bits are ignored
+        assert_eq!(decode_prime_capability(0x4), (false, false));
+    }
+
+    #[test]
+    fn vblank_request_high_crtc() {
+        use drm_ffi::drm_vblank_seq_type::*;
+
+        let relative = VblankWaitTarget::Relative(1);
+        let (type_, sequence) = vblank_request(relative, VblankWaitFlags::empty(), 0).unwrap();
+        assert_eq!((type_, sequence), (_DRM_VBLANK_RELATIVE, 1));
+
+        // The index is stored in bits 1 to 5
+        let (type_, _) = vblank_request(relative, VblankWaitFlags::empty(), 1).unwrap();
+        assert_eq!(type_, _DRM_VBLANK_RELATIVE | 0x2);
+        let (type_, _) = vblank_request(relative, VblankWaitFlags::empty(), 31).unwrap();
+        assert_eq!(
+            type_ & _DRM_VBLANK_HIGH_CRTC_MASK,
+            _DRM_VBLANK_HIGH_CRTC_MASK
+        );
+
+        // Indices not fitting into the mask are rejected
+        assert!(vblank_request(relative, VblankWaitFlags::empty(), 32).is_err());
+    }
+
+    #[test]
+    fn vblank_reply() {
+        let reply = VblankWaitReply::from(drm_ffi::drm_wait_vblank_reply {
+            type_: drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_RELATIVE,
+            sequence: 1234,
+            tval_sec: 5,
+            tval_usec: 250_000,
+        });
+        assert_eq!(reply.frame(), 1234);
+        assert_eq!(reply.sequence(), 1234);
+        assert_eq!(reply.time(), Some(Duration::from_millis(5250)));
+
+        let event = VblankWaitReply::from(drm_ffi::drm_wait_vblank_reply {
+            type_: drm_ffi::drm_vblank_seq_type::_DRM_VBLANK_EVENT,
+            sequence: 1235,
+            tval_sec: 0,
+            tval_usec: 0,
+        });
+        assert_eq!(event.sequence(), 1235);
+        assert_eq!(event.time(), None);
+    }
+
+    #[test]
+    fn vblank_request_flags() {
+        use drm_ffi::drm_vblank_seq_type::*;
+
+        let flags = VblankWaitFlags::EVENT | VblankWaitFlags::NEXT_ON_MISS;
+        let (type_, sequence) = vblank_request(VblankWaitTarget::Absolute(100), flags, 2).unwrap();
+        assert_eq!(sequence, 100);
+        assert_eq!(
+            type_,
+            _DRM_VBLANK_ABSOLUTE | _DRM_VBLANK_EVENT | _DRM_VBLANK_NEXTONMISS | (2 << 1)
+        );
+    }
+
+    #[test]
+    fn driver_version_at_least() {
+        let driver = Driver {
+            version: (1, 4, 2),
+            name: OsString::from("test"),
+            date: OsString::new(),
+            desc: OsString::new(),
+        };
+        assert!(driver.version_at_least(1, 4, 2));
+        assert!(driver.version_at_least(1, 4, 1));
+        assert!(!driver.version_at_least(1, 4, 3));
+        assert!(driver.version_at_least(1, 3, 9));
+        assert!(!driver.version_at_least(1, 5, 0));
+        assert!(driver.version_at_least(0, 9, 9));
+        assert!(!driver.version_at_least(2, 0, 0));
+    }
+
+    #[test]
+    fn bus_id_parse() {
+        let pci = BusId::Pci {
+            domain: 0,
+            bus: 3,
+            dev: 0,
+            func: 0,
+        };
+        assert_eq!(BusId::from(OsStr::new("pci:0000:03:00.0")), pci);
+        assert_eq!(BusId::from(OsStr::new("0000:03:00.0")), pci);
+        assert_eq!(
+            BusId::from(OsStr::new("pci:0001:1a:0f.3")),
+            BusId::Pci {
+                domain: 1,
+                bus: 0x1a,
+                dev: 0x0f,
+                func: 3,
+            }
+        );
+        assert_eq!(
+            BusId::from(OsStr::new("platform:vc4-drm")),
+            BusId::Platform("vc4-drm".to_owned())
+        );
+        assert_eq!(
+            BusId::from(OsStr::new("usb:3-1.2:1.0")),
+            BusId::Usb("3-1.2:1.0".to_owned())
+        );
+        assert_eq!(
+            BusId::from(OsStr::new("pci:0000:zz:00.0")),
+            BusId::Unknown(OsString::from("pci:0000:zz:00.0"))
+        );
+        assert_eq!(
+            BusId::from(OsStr::new("virtio0")),
+            BusId::Unknown(OsString::from("virtio0"))
+        );
+    }
+
+    /// Records set client capabilities and rejects [`ClientCapability::Atomic`]
+    struct CapRecorder {
+        mock: mock::MockDevice,
+        calls: std::cell::RefCell<Vec<(ClientCapability, bool)>>,
+    }
+
+    impl AsFd for CapRecorder {
+        fn as_fd(&self) -> std::os::unix::io::BorrowedFd<'_> {
+            self.mock.as_fd()
+        }
+    }
+
+    impl Device for CapRecorder {
+        fn set_client_capability(&self, cap: ClientCapability, enable: bool) -> io::Result<()> {
+            self.calls.borrow_mut().push((cap, enable));
+            if cap == ClientCapability::Atomic {
+                return Err(Errno::OPNOTSUPP.into());
+            }
+            Ok(())
+        }
+    }
+
+    fn cap_recorder() -> CapRecorder {
+        CapRecorder {
+            mock: mock::MockDevice::new().unwrap(),
+            calls: Default::default(),
+        }
+    }
+
+    #[test]
+    fn set_client_capabilities_in_order() {
+        let device = cap_recorder();
+        let caps = [
+            (ClientCapability::UniversalPlanes, true),
+            (ClientCapability::AspectRatio, false),
+            (ClientCapability::WritebackConnectors, true),
+        ];
+        device.set_client_capabilities(&caps).unwrap();
+        assert_eq!(*device.calls.borrow(), caps);
+    }
+
+    #[test]
+    fn set_client_capabilities_stops_at_error() {
+        let device = cap_recorder();
+        let err = device
+            .set_client_capabilities(&[
+                (ClientCapability::UniversalPlanes, true),
+                (ClientCapability::Atomic, true),
+                (ClientCapability::AspectRatio, true),
+            ])
+            .unwrap_err();
+        assert_eq!(err.capability, ClientCapability::Atomic);
+        assert_eq!(
+            err.source.raw_os_error(),
+            Some(Errno::OPNOTSUPP.raw_os_error())
+        );
+        assert_eq!(
+            *device.calls.borrow(),
+            [
+                (ClientCapability::UniversalPlanes, true),
+                (ClientCapability::Atomic, true),
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! An in-memory stand-in for a DRM device, for testing code built on this crate
+//! without access to real hardware.
+//!
+//! This module is only available with the `test-mock` feature enabled.
+//!
+//! # Limitations
+//!
+//! [`MockDevice`] is backed by a socket pair instead of a device node. Reading
+//! events works just like on a real device, which allows event handling code
+//! to be driven by events injected through [`MockDevice::push_event`] and
+//! friends. Any method that issues an ioctl will fail with `ENOTTY`, as the
+//! kernel does not recognize DRM requests on a socket.
+//!
+//! # Example
+//!
+//! ```
+//! use drm::control::{Device as ControlDevice, Event};
+//! use drm::mock::MockDevice;
+//!
+//! let device = MockDevice::new().unwrap();
+//! let crtc = drm::control::from_u32(42).unwrap();
+//! device.push_page_flip_event(crtc, 1, 0).unwrap();
+//!
+//! let mut events = device.receive_events().unwrap();
+//! assert!(matches!(events.next(), Some(Event::PageFlip(event)) if event.crtc == crtc));
+//! ```
+
+use crate::control;
+use drm_ffi as ffi;
+
+use std::io::{self, Write};
+use std::mem;
+use std::os::unix::io::{AsFd, BorrowedFd};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+/// A fake DRM device that produces injected events.
+#[derive(Debug)]
+pub struct MockDevice {
+    card: UnixStream,
+    peer: UnixStream,
+}
+
+impl MockDevice {
+    /// Creates a new mock device without any pending events.
+    pub fn new() -> io::Result<MockDevice> {
+        let (card, peer) = UnixStream::pair()?;
+        Ok(MockDevice { card, peer })
+    }
+
+    /// Queues raw event data, to be returned by the next read of the device.
+    pub fn push_event(&self, data: &[u8]) -> io::Result<()> {
+        (&self.peer).write_all(data)
+    }
+
+    /// Queues a vblank event, as requested by [`crate::Device::wait_vblank`].
+    pub fn push_vblank_event(
+        &self,
+        crtc: control::crtc::Handle,
+        frame: u32,
+        time: Duration,
+        user_data: u64,
+    ) -> io::Result<()> {
+        self.push_vblank_like(ffi::DRM_EVENT_VBLANK, crtc.into(), frame, time, user_data)
+    }
+
+    /// Queues a page flip completion event, as requested by
+    /// [`control::Device::page_flip`] or [`control::Device::atomic_commit`].
+    pub fn push_page_flip_event(
+        &self,
+        crtc: control::crtc::Handle,
+        frame: u32,
+        user_data: u64,
+    ) -> io::Result<()> {
+        self.push_vblank_like(
+            ffi::DRM_EVENT_FLIP_COMPLETE,
+            crtc.into(),
+            frame,
+            Duration::ZERO,
+            user_data,
+        )
+    }
+
+    fn push_vblank_like(
+        &self,
+        type_: u32,
+        crtc_id: u32,
+        sequence: u32,
+        time: Duration,
+        user_data: u64,
+    ) -> io::Result<()> {
+        let event = ffi::drm_event_vblank {
+            base: ffi::drm_event {
+                type_,
+                length: mem::size_of::<ffi::drm_event_vblank>() as u32,
+            },
+            user_data,
+            tv_sec: time.as_secs() as u32,
+            tv_usec: time.subsec_micros(),
+            sequence,
+            crtc_id,
+        };
+        // SAFETY: drm_event_vblank is a plain repr(C) struct without padding
+        let data = unsafe {
+            std::slice::from_raw_parts(
+                &event as *const _ as *const u8,
+                mem::size_of::<ffi::drm_event_vblank>(),
+            )
+        };
+        self.push_event(data)
+    }
+}
+
+impl AsFd for MockDevice {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.card.as_fd()
+    }
+}
+
+impl crate::Device for MockDevice {}
+impl control::Device for MockDevice {}
+
+#[cfg(test)]
+mod tests {
+    use super::MockDevice;
+    use crate::control::{from_u32, Device as ControlDevice, Event, EventReader};
+    use std::time::Duration;
+
+    #[test]
+    fn vblank_event() {
+        let device = MockDevice::new().unwrap();
+        let crtc = from_u32(42).unwrap();
+        let time = Duration::new(12, 345_000);
+        device.push_vblank_event(crtc, 7, time, 99).unwrap();
+
+        let mut events = device.receive_events().unwrap();
+        match events.next() {
+            Some(Event::Vblank(event)) => {
+                assert_eq!(event.crtc, crtc);
+                assert_eq!(event.frame, 7);
+                assert_eq!(event.time, time);
+                assert_eq!(event.user_data, 99);
+            }
+            _ => panic!("expected a vblank event"),
+        }
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn page_flip_events() {
+        let device = MockDevice::new().unwrap();
+        let first = from_u32(1).unwrap();
+        let second = from_u32(2).unwrap();
+        device.push_page_flip_event(first, 10, 100).unwrap();
+        device.push_page_flip_event(second, 11, 200).unwrap();
+
+        let events: Vec<_> = device
+            .receive_events()
+            .unwrap()
+            .map(|event| match event {
+                Event::PageFlip(event) => (event.crtc, event.frame, event.user_data),
+                _ => panic!("expected a page flip event"),
+            })
+            .collect();
+        assert_eq!(events, [(first, 10, 100), (second, 11, 200)]);
+    }
+
+    #[test]
+    fn unknown_event() {
+        let device = MockDevice::new().unwrap();
+        // type 0x12345678, length 12
+        let data = [0x78, 0x56, 0x34, 0x12, 12, 0, 0, 0, 1, 2, 3, 4];
+        device.push_event(&data).unwrap();
+
+        let mut events = device.receive_events().unwrap();
+        assert!(matches!(events.next(), Some(Event::Unknown(raw)) if raw == data));
+    }
+
+    #[test]
+    fn event_reader_retains_partial_events() {
+        let device = MockDevice::new().unwrap();
+        let crtc = from_u32(3).unwrap();
+        device.push_page_flip_event(crtc, 1, 0).unwrap();
+        // Header of an incomplete second event of unknown type
+        device.push_event(&[0, 0x10, 0, 0, 16, 0, 0, 0]).unwrap();
+
+        let mut reader = EventReader::new(&device);
+        let mut events = reader.poll_events().unwrap();
+        assert!(matches!(events.next(), Some(Event::PageFlip(event)) if event.crtc == crtc));
+        assert!(events.next().is_none());
+
+        device.push_event(&[0xff; 8]).unwrap();
+        let mut events = reader.poll_events().unwrap();
+        assert!(matches!(events.next(), Some(Event::Unknown(raw)) if raw.len() == 16));
+        assert!(events.next().is_none());
+    }
+
+    #[test]
+    fn ioctls_are_unsupported() {
+        let device = MockDevice::new().unwrap();
+        let err = device.resource_handles().unwrap_err();
+        assert_eq!(
+            err.raw_os_error(),
+            Some(rustix::io::Errno::NOTTY.raw_os_error())
+        );
+    }
+}
@@ -5,10 +5,10 @@ pub mod constants;
 use std::error::Error;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::io;
-use std::os::unix::io::AsFd;
+use std::os::unix::io::{AsFd, OwnedFd};
 use std::path::{Path, PathBuf};
 
-use rustix::fs::{fstat, major, minor, stat, Dev as dev_t, Stat};
+use rustix::fs::{fstat, major, minor, stat, Dev as dev_t, Mode, OFlags, Stat};
 
 use crate::node::constants::*;
 
@@ -44,6 +44,12 @@ impl DrmNode {
             return Err(CreateDrmNodeError::NotDrmNode);
         }
 
+        DrmNode::classify(dev).ok_or(CreateDrmNodeError::NotDrmNode)
+    }
+
+    /// Determines the type of a node from its minor number, without checking
+    /// whether `dev` refers to a DRM device.
+    fn classify(dev: dev_t) -> Option<DrmNode> {
         // The type of the DRM node is determined by the minor number ranges:
         //   0 -  63 -> Primary
         //  64 - 127 -> Control
@@ -52,10 +58,10 @@ impl DrmNode {
             0 => NodeType::Primary,
             1 => NodeType::Control,
             2 => NodeType::Render,
-            _ => return Err(CreateDrmNodeError::NotDrmNode),
+            _ => return None,
         };
 
-        Ok(DrmNode { dev, ty })
+        Some(DrmNode { dev, ty })
     }
 
     /// Returns the type of the DRM node.
@@ -93,6 +99,15 @@ impl DrmNode {
         minor(self.dev_id())
     }
 
+    /// Returns the index of the node within its type, e.g. `0` for both
+    /// `card0` and `renderD128`.
+    ///
+    /// Nodes of different types with the same index usually belong to the same
+    /// DRM device. [`DrmNode::node_with_type`] finds related nodes reliably.
+    pub fn index(&self) -> u32 {
+        self.minor() & 0b11_1111
+    }
+
     /// Returns whether the DRM device has render nodes.
     pub fn has_render(&self) -> bool {
         #[cfg(target_os = "linux")]
@@ -151,7 +166,6 @@ impl NodeType {
         }
     }
 
-    #[cfg(not(target_os = "linux"))]
     fn minor_base(&self) -> u32 {
         match self {
             NodeType::Primary => 0,
@@ -167,6 +181,212 @@ impl Display for NodeType {
     }
 }
 
+bitflags::bitflags! {
+    /// Additional flags for opening a node using [`open`]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct OpenFlags: u32 {
+        /// Open the node in non-blocking mode, so reading without pending events
+        /// fails with `EAGAIN` instead of blocking
+        const NONBLOCK = 1 << 0;
+    }
+}
+
+/// Information about the device of a DRM node, as exposed by sysfs.
+///
+/// Returned by [`sysfs_device_info`]. Ids are only available for PCI devices.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeviceSysfsInfo {
+    /// Vendor id of the device
+    pub vendor: Option<u32>,
+    /// Device id of the device
+    pub device: Option<u32>,
+    /// Subsystem vendor id of the device
+    pub subsystem_vendor: Option<u32>,
+    /// Subsystem device id of the device
+    pub subsystem_device: Option<u32>,
+    /// Name of the kernel driver bound to the device
+    pub driver: Option<String>,
+}
+
+/// Reads information about the device of a DRM node from sysfs, without
+/// opening the node.
+#[cfg(target_os = "linux")]
+pub fn sysfs_device_info(dev: dev_t) -> io::Result<DeviceSysfsInfo> {
+    use std::io::ErrorKind;
+
+    if !is_device_drm(dev) {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("{}:{} is no DRM device", major(dev), minor(dev)),
+        ));
+    }
+
+    Ok(sysfs_device_info_at(Path::new("/sys"), dev))
+}
+
+/// Reads the information of [`sysfs_device_info`] from a sysfs tree mounted
+/// at `root`.
+#[cfg(target_os = "linux")]
+fn sysfs_device_info_at(root: &Path, dev: dev_t) -> DeviceSysfsInfo {
+    use std::fs;
+
+    let device = root.join(format!("dev/char/{}:{}/device", major(dev), minor(dev)));
+    let read_id = |name: &str| {
+        let value = fs::read_to_string(device.join(name)).ok()?;
+        let value = value.trim();
+        u32::from_str_radix(value.strip_prefix("0x").unwrap_or(value), 16).ok()
+    };
+    let driver = fs::read_link(device.join("driver"))
+        .ok()
+        .and_then(|driver| Some(driver.file_name()?.to_string_lossy().into_owned()));
+
+    DeviceSysfsInfo {
+        vendor: read_id("vendor"),
+        device: read_id("device"),
+        subsystem_vendor: read_id("subsystem_vendor"),
+        subsystem_device: read_id("subsystem_device"),
+        driver,
+    }
+}
+
+/// Reads information about the device of a DRM node from sysfs, without
+/// opening the node.
+///
+/// sysfs is only available on Linux, so this always fails with
+/// [`io::ErrorKind::Unsupported`].
+#[cfg(not(target_os = "linux"))]
+pub fn sysfs_device_info(_dev: dev_t) -> io::Result<DeviceSysfsInfo> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// The nodes of a single DRM device, as listed by [`enumerate`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeviceNodes {
+    primary: Option<DrmNode>,
+    control: Option<DrmNode>,
+    render: Option<DrmNode>,
+}
+
+impl DeviceNodes {
+    /// Returns the primary node of the device, e.g. `card0`.
+    pub fn primary(&self) -> Option<DrmNode> {
+        self.primary
+    }
+
+    /// Returns the control node of the device, e.g. `controlD64`.
+    pub fn control(&self) -> Option<DrmNode> {
+        self.control
+    }
+
+    /// Returns the render node of the device, e.g. `renderD128`.
+    pub fn render(&self) -> Option<DrmNode> {
+        self.render
+    }
+
+    /// Returns the node of the given type.
+    pub fn node(&self, ty: NodeType) -> Option<DrmNode> {
+        match ty {
+            NodeType::Primary => self.primary,
+            NodeType::Control => self.control,
+            NodeType::Render => self.render,
+        }
+    }
+
+    /// Returns the nodes of the device, primary node first.
+    pub fn nodes(&self) -> impl Iterator<Item = DrmNode> {
+        [self.primary, self.control, self.render]
+            .into_iter()
+            .flatten()
+    }
+
+    fn slot(&mut self, ty: NodeType) -> &mut Option<DrmNode> {
+        match ty {
+            NodeType::Primary => &mut self.primary,
+            NodeType::Control => &mut self.control,
+            NodeType::Render => &mut self.render,
+        }
+    }
+}
+
+/// Lists the DRM devices with nodes in `/dev/dri`.
+///
+/// Nodes are grouped into devices like by [`enumerate_from`]. Entries that
+/// are not DRM nodes are skipped.
+pub fn enumerate() -> io::Result<Vec<DeviceNodes>> {
+    let mut devs = Vec::new();
+    for entry in std::fs::read_dir("/dev/dri")? {
+        match DrmNode::from_path(entry?.path()) {
+            Ok(node) => devs.push(node.dev_id()),
+            Err(CreateDrmNodeError::NotDrmNode) => {}
+            Err(CreateDrmNodeError::Io(err)) => return Err(err),
+        }
+    }
+    Ok(enumerate_from(devs))
+}
+
+/// Groups DRM nodes, given by their device numbers, into devices.
+///
+/// Each node is classified by its minor number, and nodes with the same major
+/// number and [`DrmNode::index`] are considered to belong to the same device.
+/// Device numbers are not checked to refer to DRM devices, but those outside
+/// of the minor number ranges of DRM nodes are skipped, as are duplicates.
+///
+/// The devices are sorted by their major number and index.
+pub fn enumerate_from<I>(devs: I) -> Vec<DeviceNodes>
+where
+    I: IntoIterator<Item = dev_t>,
+{
+    let mut nodes: Vec<DrmNode> = devs.into_iter().filter_map(DrmNode::classify).collect();
+    nodes.sort_by_key(|node| (node.major(), node.index(), node.ty().minor_base()));
+    nodes.dedup();
+
+    let mut devices: Vec<((u32, u32), DeviceNodes)> = Vec::new();
+    for node in nodes {
+        let key = (node.major(), node.index());
+        let device = match devices.last_mut() {
+            Some((last, device)) if *last == key => device,
+            _ => {
+                devices.push((key, DeviceNodes::default()));
+                &mut devices.last_mut().unwrap().1
+            }
+        };
+        *device.slot(node.ty()) = Some(node);
+    }
+    devices.into_iter().map(|(_, device)| device).collect()
+}
+
+/// Opens a DRM node for reading and writing.
+///
+/// The file descriptor is always opened with `O_CLOEXEC`, so it is not leaked
+/// into child processes.
+pub fn open<P: AsRef<Path>>(path: P, flags: OpenFlags) -> io::Result<OwnedFd> {
+    let mut oflags = OFlags::RDWR | OFlags::CLOEXEC;
+    if flags.contains(OpenFlags::NONBLOCK) {
+        oflags |= OFlags::NONBLOCK;
+    }
+    Ok(rustix::fs::open(path.as_ref(), oflags, Mode::empty())?)
+}
+
+/// Opens the `n`-th primary node, e.g. `/dev/dri/card0`, like [`open`].
+pub fn open_primary(n: u32, flags: OpenFlags) -> io::Result<OwnedFd> {
+    open(numbered_path(NodeType::Primary, n), flags)
+}
+
+/// Opens the `n`-th render node, e.g. `/dev/dri/renderD128`, like [`open`].
+pub fn open_render(n: u32, flags: OpenFlags) -> io::Result<OwnedFd> {
+    open(numbered_path(NodeType::Render, n), flags)
+}
+
+/// Returns the path of the `n`-th node of a type, counting from the first
+/// minor number of the type.
+fn numbered_path(ty: NodeType, n: u32) -> PathBuf {
+    PathBuf::from(format!(
+        "/dev/dri/{}{}",
+        ty.minor_name_prefix(),
+        ty.minor_base() + n
+    ))
+}
+
 /// An error that may occur when creating a [`DrmNode`] from a file descriptor.
 #[derive(Debug)]
 pub enum CreateDrmNodeError {
@@ -380,3 +600,137 @@ pub fn dev_path(dev: dev_t, ty: NodeType) -> io::Result<PathBuf> {
         ),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustix::fs::makedev;
+
+    #[test]
+    fn classify_nodes() {
+        let node = DrmNode::classify(makedev(226, 1)).unwrap();
+        assert_eq!(node.ty(), NodeType::Primary);
+        assert_eq!(node.index(), 1);
+
+        let node = DrmNode::classify(makedev(226, 65)).unwrap();
+        assert_eq!(node.ty(), NodeType::Control);
+        assert_eq!(node.index(), 1);
+
+        let node = DrmNode::classify(makedev(226, 129)).unwrap();
+        assert_eq!(node.ty(), NodeType::Render);
+        assert_eq!(node.index(), 1);
+
+        assert_eq!(DrmNode::classify(makedev(226, 192)), None);
+    }
+
+    #[test]
+    fn group_nodes() {
+        let devices = enumerate_from([
+            makedev(226, 129),
+            makedev(226, 1),
+            makedev(226, 128),
+            makedev(226, 0),
+            makedev(226, 64),
+            makedev(226, 200),
+        ]);
+        assert_eq!(devices.len(), 2);
+
+        let first = &devices[0];
+        assert_eq!(first.primary().map(|node| node.minor()), Some(0));
+        assert_eq!(first.control().map(|node| node.minor()), Some(64));
+        assert_eq!(first.render().map(|node| node.minor()), Some(128));
+        assert_eq!(
+            first.nodes().map(|node| node.minor()).collect::<Vec<_>>(),
+            [0, 64, 128]
+        );
+
+        let second = &devices[1];
+        assert_eq!(
+            second.node(NodeType::Primary).map(|node| node.minor()),
+            Some(1)
+        );
+        assert_eq!(second.node(NodeType::Control), None);
+        assert_eq!(
+            second.node(NodeType::Render).map(|node| node.minor()),
+            Some(129)
+        );
+    }
+
+    #[test]
+    fn group_render_only_and_duplicates() {
+        let devices = enumerate_from([makedev(226, 130), makedev(226, 130), makedev(227, 2)]);
+        assert_eq!(devices.len(), 2);
+        // Sorted by major first
+        assert_eq!(devices[0].render().map(|node| node.minor()), Some(130));
+        assert_eq!(devices[0].primary(), None);
+        assert_eq!(devices[1].primary().map(|node| node.major()), Some(227));
+        assert_eq!(devices[1].render(), None);
+    }
+
+    #[test]
+    fn numbered_paths() {
+        assert_eq!(
+            numbered_path(NodeType::Primary, 0),
+            Path::new("/dev/dri/card0")
+        );
+        assert_eq!(
+            numbered_path(NodeType::Control, 1),
+            Path::new("/dev/dri/controlD65")
+        );
+        assert_eq!(
+            numbered_path(NodeType::Render, 0),
+            Path::new("/dev/dri/renderD128")
+        );
+    }
+
+    #[test]
+    fn open_sets_cloexec() {
+        use rustix::io::{fcntl_getfd, FdFlags};
+
+        let path = std::env::temp_dir().join(format!("drm-rs-open-{}", std::process::id()));
+        std::fs::write(&path, b"").unwrap();
+        let fd = open(&path, OpenFlags::NONBLOCK);
+        std::fs::remove_file(&path).unwrap();
+
+        let flags = fcntl_getfd(fd.unwrap()).unwrap();
+        assert!(flags.contains(FdFlags::CLOEXEC));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sysfs_device_info_fixture() {
+        let root = std::env::temp_dir().join(format!("drm-rs-sysfs-{}", std::process::id()));
+        let device = root.join("dev/char/226:0/device");
+        std::fs::create_dir_all(&device).unwrap();
+        std::fs::write(device.join("vendor"), "0x8086\n").unwrap();
+        std::fs::write(device.join("device"), "0x46a6\n").unwrap();
+        std::fs::write(device.join("subsystem_vendor"), "17aa").unwrap();
+        std::fs::write(device.join("subsystem_device"), "garbage\n").unwrap();
+        std::os::unix::fs::symlink("../../../bus/pci/drivers/i915", device.join("driver")).unwrap();
+
+        let info = sysfs_device_info_at(&root, makedev(226, 0));
+        let other = sysfs_device_info_at(&root, makedev(226, 1));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            info,
+            DeviceSysfsInfo {
+                vendor: Some(0x8086),
+                device: Some(0x46a6),
+                subsystem_vendor: Some(0x17aa),
+                subsystem_device: None,
+                driver: Some("i915".to_owned()),
+            }
+        );
+        assert_eq!(
+            other,
+            DeviceSysfsInfo {
+                vendor: None,
+                device: None,
+                subsystem_vendor: None,
+                subsystem_device: None,
+                driver: None,
+            }
+        );
+    }
+}